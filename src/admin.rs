@@ -0,0 +1,98 @@
+//! Admin client of a running instance, for [`Command::Cache`]
+//!
+//! [`Command::Cache`]: crate::config::Command::Cache
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use anyhow::{Context, Result, bail};
+use reqwest::{Method, StatusCode, Url};
+
+use crate::{
+    config::{CacheAction, CacheTarget, Config, ListenAddr},
+    utils::GENERAL_USER_AGENT,
+};
+
+/// Run the given cache action against the running instance.
+pub(crate) async fn run(config: &Config, server: Option<&str>, action: &CacheAction) -> Result<()> {
+    let server = match server {
+        Some(server) => Url::parse(server).with_context(|| format!("Invalid server `{server}`"))?,
+        None => default_server(config)?,
+    };
+
+    let (method, segments) = match action {
+        CacheAction::List => (Method::GET, vec!["admin", "cache"]),
+        CacheAction::Show(target) => (Method::GET, user_segments(config, target)),
+        CacheAction::Purge(target) => (Method::DELETE, user_segments(config, target)),
+        CacheAction::Refresh(target) => {
+            let mut segments = user_segments(config, target);
+            segments.push("refresh");
+            (Method::POST, segments)
+        }
+        CacheAction::ClearInFlight => (Method::DELETE, vec!["admin", "in-flight"]),
+    };
+
+    let mut url = server;
+    url.path_segments_mut()
+        .map_err(|()| anyhow::anyhow!("Invalid server, cannot be a base URL"))?
+        .pop_if_empty()
+        .extend(segments);
+
+    let mut request = reqwest::Client::builder()
+        .user_agent(GENERAL_USER_AGENT)
+        .build()?
+        .request(method, url);
+
+    if let Some(access_key) = &config.access_key {
+        request = request.header("x-access-key", access_key.expose().as_str());
+    }
+
+    let response = request
+        .send()
+        .await
+        .context("Request the running instance error")?;
+
+    let status = response.status();
+    let body = response.text().await?;
+
+    match status {
+        StatusCode::NO_CONTENT => eprintln!("Done"),
+        status if status.is_success() => println!("{body}"),
+        StatusCode::FORBIDDEN => bail!("Forbidden, check `access_key` of the config"),
+        StatusCode::NOT_FOUND => {
+            bail!("Not found: not cached, unknown forum, or the card feature is disabled")
+        }
+        status => bail!("Unexpected response: {status} {body}"),
+    }
+
+    Ok(())
+}
+
+/// Path segments of the given user.
+fn user_segments<'a>(config: &'a Config, target: &'a CacheTarget) -> Vec<&'a str> {
+    let forum = target
+        .forum
+        .as_deref()
+        .or_else(|| config.forums.first().map(|forum| forum.name.as_ref()))
+        .unwrap_or_default();
+
+    vec!["admin", "cache", forum, &target.user]
+}
+
+/// The first listen address, unspecified IP is replaced with the loopback one.
+fn default_server(config: &Config) -> Result<Url> {
+    let Some(listen) = config.listen.first() else {
+        bail!("No listen address in the config, specify `--server`")
+    };
+
+    let ListenAddr::SocketAddr(mut addr) = listen.clone() else {
+        bail!("Unix socket `{listen}` is not supported, specify `--server`")
+    };
+
+    match addr.ip() {
+        IpAddr::V4(ip) if ip.is_unspecified() => addr.set_ip(Ipv4Addr::LOCALHOST.into()),
+        IpAddr::V6(ip) if ip.is_unspecified() => addr.set_ip(Ipv6Addr::LOCALHOST.into()),
+        _ => {}
+    }
+
+    Ok(Url::parse(&format!("http://{addr}"))?)
+}
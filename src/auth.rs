@@ -0,0 +1,405 @@
+//! Login with Discourse Connect, for card and counter ownership
+//!
+//! The forum acts as the identity provider, see
+//! <https://meta.discourse.org/t/discourseconnect-official-single-sign-on-for-discourse-sso/13045>:
+//!
+//! 1. `/auth/login/{forum}` redirects to the forum with a signed nonce.
+//! 2. The forum redirects back to `/auth/callback/{forum}` with the signed user
+//!    info, then a session is created.
+//!
+//! Sessions are kept in memory only, login again after restarting.
+
+use std::{
+    sync::{Arc, LazyLock},
+    time::{Duration, Instant},
+};
+
+use axum::http::{HeaderMap, header::COOKIE};
+use dashmap::DashMap;
+use macro_toolset::{
+    str_concat,
+    string::{StringExtT, base64::b64_padding},
+    urlencoding_str,
+};
+use rand::Rng;
+use ring::hmac;
+
+use crate::{
+    config::{CONF_PUBLIC_URL, Forum},
+    utils::Queries,
+};
+
+/// Nonce TTL, 600s
+const NONCE_TTL: u64 = 600;
+
+/// Max pending logins, new ones are refused when full
+const MAX_PENDING_LOGINS: usize = 1024;
+
+/// Session TTL, 7 days
+pub(crate) const SESSION_TTL: u64 = 7 * 24 * 3600;
+
+/// Session cookie name
+pub(crate) const SESSION_COOKIE: &str = "greeting_session";
+
+/// Pending logins: nonce -> (forum name, when)
+type Nonces = DashMap<Arc<str>, (Arc<str>, Instant), foldhash::fast::RandomState>;
+
+/// Pending logins
+static NONCES: LazyLock<Nonces> = LazyLock::new(DashMap::default);
+
+/// Sessions: token -> session
+static SESSIONS: LazyLock<DashMap<Arc<str>, Session, foldhash::fast::RandomState>> =
+    LazyLock::new(DashMap::default);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Logged in user
+pub(crate) struct Session {
+    /// Forum name
+    pub forum: Arc<str>,
+
+    /// Username, as returned by the forum
+    pub user: Arc<str>,
+
+    /// When logged in
+    created: Instant,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+/// Login error
+pub(crate) enum LoginError {
+    #[error("login with forum `{0}` is not enabled")]
+    /// No `connect_secret` of the forum, or no `public_url`
+    NotEnabled(Arc<str>),
+
+    #[error("invalid signature")]
+    /// Signature mismatch
+    InvalidSignature,
+
+    #[error("too many pending logins")]
+    /// Pending logins reached [`MAX_PENDING_LOGINS`]
+    TooManyPending,
+
+    #[error("invalid or expired nonce")]
+    /// Unknown, expired or replayed nonce
+    InvalidNonce,
+
+    #[error("invalid payload, missing `{0}`")]
+    /// Missing field in the payload
+    InvalidPayload(&'static str),
+}
+
+/// The URL to redirect the user to for login with the forum.
+pub(crate) fn login_url(forum: &Forum) -> Result<String, LoginError> {
+    let (secret, public_url) = enabled(forum)?;
+
+    if NONCES.len() >= MAX_PENDING_LOGINS {
+        NONCES.retain(|_, v| v.1.elapsed().as_secs() <= NONCE_TTL);
+
+        if NONCES.len() >= MAX_PENDING_LOGINS {
+            return Err(LoginError::TooManyPending);
+        }
+    }
+
+    let nonce: Arc<str> = Arc::from(random_token());
+    NONCES.insert(nonce.clone(), (forum.name.clone(), Instant::now()));
+
+    let return_url = str_concat!(
+        public_url.trim_end_matches('/'),
+        "/auth/callback/",
+        urlencoding_str!(E: forum.name.as_ref())
+    );
+
+    let payload = b64_padding::STANDARD::encode(str_concat!(
+        "nonce=",
+        &nonce,
+        "&return_sso_url=",
+        urlencoding_str!(E: return_url.as_str())
+    ))
+    .to_string_ext();
+
+    Ok(str_concat!(
+        forum.url("/session/sso_provider"),
+        "?sso=",
+        urlencoding_str!(E: payload.as_str()),
+        "&sig=",
+        sign(secret, &payload)
+    ))
+}
+
+/// Verify the callback from the forum, returning the new session and its
+/// token.
+pub(crate) fn callback(
+    forum: &Forum,
+    sso: &str,
+    sig: &str,
+) -> Result<(Arc<str>, Session), LoginError> {
+    let (secret, _) = enabled(forum)?;
+
+    if !verify(secret, sso, sig) {
+        return Err(LoginError::InvalidSignature);
+    }
+
+    let payload = b64_padding::STANDARD::decode(sso).to_string_ext();
+    let payload = Queries::try_parse(&payload);
+
+    let nonce = payload
+        .get("nonce")
+        .ok_or(LoginError::InvalidPayload("nonce"))?;
+
+    NONCES
+        .remove(nonce.as_ref())
+        .filter(|(_, (forum_name, created))| {
+            *forum_name == forum.name && created.elapsed().as_secs() <= NONCE_TTL
+        })
+        .ok_or(LoginError::InvalidNonce)?;
+
+    let user = payload
+        .get("username")
+        .filter(|user| !user.is_empty())
+        .ok_or(LoginError::InvalidPayload("username"))?;
+
+    let token: Arc<str> = Arc::from(random_token());
+
+    if SESSIONS.len() > 1024 {
+        SESSIONS.retain(|_, session| session.created.elapsed().as_secs() <= SESSION_TTL);
+    }
+    let session = Session {
+        forum: forum.name.clone(),
+        user: Arc::from(user.as_ref()),
+        created: Instant::now(),
+    };
+    SESSIONS.insert(token.clone(), session.clone());
+
+    tracing::info!(
+        forum = forum.name.as_ref(),
+        user = user.as_ref(),
+        "Logged in"
+    );
+
+    Ok((token, session))
+}
+
+/// Get the session of the request, by the session cookie.
+pub(crate) fn session(headers: &HeaderMap) -> Option<(Arc<str>, Session)> {
+    let token = session_token(headers)?;
+    let session = SESSIONS.get(token)?.clone();
+
+    if session.created.elapsed() > Duration::from_secs(SESSION_TTL) {
+        SESSIONS.remove(token);
+
+        return None;
+    }
+
+    Some((Arc::from(token), session))
+}
+
+/// Logout, removing the session.
+pub(crate) fn logout(token: &str) {
+    SESSIONS.remove(token);
+}
+
+/// `Set-Cookie` value of the session cookie, empty token to clear it.
+pub(crate) fn session_cookie(token: &str) -> String {
+    let secure = CONF_PUBLIC_URL
+        .load()
+        .as_deref()
+        .is_some_and(|public_url| public_url.starts_with("https://"));
+
+    str_concat!(
+        SESSION_COOKIE,
+        "=",
+        token,
+        "; Path=/; Max-Age=",
+        if token.is_empty() { 0 } else { SESSION_TTL },
+        "; HttpOnly; SameSite=Lax",
+        secure.then_some("; Secure")
+    )
+}
+
+/// The session token in the `Cookie` header.
+fn session_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get_all(COOKIE)
+        .iter()
+        .filter_map(|cookie| cookie.to_str().ok())
+        .flat_map(|cookie| cookie.split(';'))
+        .find_map(|pair| {
+            pair.trim()
+                .strip_prefix(SESSION_COOKIE)
+                .and_then(|v| v.strip_prefix('='))
+        })
+        .filter(|token| !token.is_empty())
+}
+
+#[inline]
+/// The secret of the forum and the public URL, if login is enabled.
+fn enabled(forum: &Forum) -> Result<(&str, Arc<str>), LoginError> {
+    forum
+        .connect_secret
+        .as_ref()
+        .map(|secret| secret.expose().as_str())
+        .zip(CONF_PUBLIC_URL.load().as_ref().clone())
+        .ok_or_else(|| LoginError::NotEnabled(forum.name.clone()))
+}
+
+/// Random token, 32 bytes, URL safe base64 encoded.
+fn random_token() -> String {
+    let bytes: [u8; 32] = rand::rng().random();
+
+    b64_padding::URL_SAFE_NO_PAD::encode(bytes).to_string_ext()
+}
+
+/// HMAC-SHA256 of the payload, hex encoded.
+fn sign(secret: &str, payload: &str) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+
+    hmac::sign(&key, payload.as_bytes()).as_ref().iter().fold(
+        String::with_capacity(64),
+        |mut hex, byte| {
+            hex.push(char::from_digit(u32::from(byte >> 4), 16).unwrap_or('0'));
+            hex.push(char::from_digit(u32::from(byte & 15), 16).unwrap_or('0'));
+            hex
+        },
+    )
+}
+
+/// Verify the hex encoded HMAC-SHA256 signature, in constant time.
+fn verify(secret: &str, payload: &str, sig: &str) -> bool {
+    if sig.len() != 64 || !sig.is_ascii() {
+        return false;
+    }
+
+    let Some(sig) = (0..sig.len())
+        .step_by(2)
+        .map(|idx| u8::from_str_radix(&sig[idx..idx + 2], 16).ok())
+        .collect::<Option<Vec<u8>>>()
+    else {
+        return false;
+    };
+
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+
+    hmac::verify(&key, payload.as_bytes(), &sig).is_ok()
+}
+
+#[tokio::test]
+async fn test_login() {
+    use axum::{
+        http::{StatusCode, header::LOCATION},
+        response::IntoResponse,
+        routing::get,
+    };
+
+    use crate::config::Secret;
+
+    const SECRET: &str = "test-connect-secret";
+
+    // Mock identity provider, like Discourse does.
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        let app = axum::Router::new().route(
+            "/session/sso_provider",
+            get(|request: axum::extract::Request| async move {
+                let queries = Queries::try_parse_uri(request.uri());
+                let (Some(sso), Some(sig)) = (queries.get("sso"), queries.get("sig")) else {
+                    return StatusCode::BAD_REQUEST.into_response();
+                };
+
+                if sign(SECRET, sso) != *sig {
+                    return StatusCode::FORBIDDEN.into_response();
+                }
+
+                let payload = b64_padding::STANDARD::decode(sso.as_ref()).to_string_ext();
+                let payload = Queries::try_parse(&payload);
+
+                let response = b64_padding::STANDARD::encode(str_concat!(
+                    "nonce=",
+                    payload.get("nonce").unwrap().as_ref(),
+                    "&external_id=1&username=Tester&name=Test%20User"
+                ))
+                .to_string_ext();
+
+                (
+                    StatusCode::SEE_OTHER,
+                    [(
+                        LOCATION,
+                        str_concat!(
+                            payload.get("return_sso_url").unwrap().as_ref(),
+                            "?sso=",
+                            urlencoding_str!(E: response.as_str()),
+                            "&sig=",
+                            sign(SECRET, &response)
+                        ),
+                    )],
+                )
+                    .into_response()
+            }),
+        );
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    CONF_PUBLIC_URL.store(Arc::new(Some(Arc::from("http://greeting.test"))));
+
+    let mut forum = Forum::default_list().remove(0).as_ref().clone();
+    forum.name = Arc::from("test-connect");
+    forum.base_url = Arc::from(format!("http://{addr}"));
+
+    assert_eq!(
+        login_url(&forum),
+        Err(LoginError::NotEnabled(forum.name.clone()))
+    );
+
+    forum.connect_secret = Some(Secret::from(SECRET.to_string()));
+
+    let response = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .unwrap()
+        .get(login_url(&forum).unwrap())
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::SEE_OTHER);
+
+    let location = response.headers()[LOCATION].to_str().unwrap();
+    let (return_url, query) = location.split_once('?').unwrap();
+    assert_eq!(
+        return_url,
+        "http://greeting.test/auth/callback/test-connect"
+    );
+
+    let queries = Queries::try_parse(query);
+    let (sso, sig) = (&queries["sso"], &queries["sig"]);
+
+    // Tampered
+    assert_eq!(
+        callback(&forum, sso, &sign("wrong-secret", sso)),
+        Err(LoginError::InvalidSignature)
+    );
+
+    let (token, _) = callback(&forum, sso, sig).unwrap();
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        COOKIE,
+        format!("a=b; {SESSION_COOKIE}={token}").parse().unwrap(),
+    );
+    let (_, session) = session(&headers).unwrap();
+    assert_eq!(session.forum.as_ref(), "test-connect");
+    assert_eq!(session.user.as_ref(), "Tester");
+
+    // Replayed
+    assert_eq!(callback(&forum, sso, sig), Err(LoginError::InvalidNonce));
+
+    logout(&token);
+    assert!(self::session(&headers).is_none());
+
+    // Pending logins are capped
+    let refused = (0..=MAX_PENDING_LOGINS)
+        .map(|_| login_url(&forum))
+        .find(Result::is_err);
+    assert_eq!(refused, Some(Err(LoginError::TooManyPending)));
+    NONCES.clear();
+}
@@ -1,7 +1,8 @@
 use std::{
+    fmt,
     fs::File,
     net::SocketAddr,
-    path::Path,
+    path::{Path, PathBuf},
     str::FromStr,
     sync::{
         Arc, LazyLock, OnceLock,
@@ -9,13 +10,19 @@ use std::{
     },
 };
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
 use arc_swap::ArcSwap;
+use chrono_tz::Tz;
 use cidr::IpCidr;
-use clap::Parser;
+use clap::{
+    ArgAction, ArgMatches, Args, CommandFactory, FromArgMatches, Parser, Subcommand,
+    parser::ValueSource,
+};
 use dashmap::DashSet;
 use serde::{Deserialize, Serialize};
 
+use crate::svg::{BgType, background, moe_counter, time_of_day::GreetingHours};
+
 // === Configs ===
 
 /// New counter `access_key`
@@ -25,10 +32,42 @@ pub(crate) static CONF_MAX_COUNTERS: AtomicUsize = AtomicUsize::new(131072);
 /// CIDR Whitelist
 pub(crate) static CONF_CIDR_WHITELIST: LazyLock<DashSet<IpCidr, foldhash::fast::RandomState>> =
     LazyLock::new(DashSet::default);
+/// Feature toggles
+pub(crate) static CONF_FEATURES: LazyLock<ArcSwap<Features>> = LazyLock::new(ArcSwap::default);
+/// Defaults when query parameters are missing
+pub(crate) static CONF_DEFAULTS: LazyLock<ArcSwap<Defaults>> = LazyLock::new(ArcSwap::default);
+/// Which Discourse users may be fetched from upstream
+pub(crate) static CONF_FETCH_POLICY: LazyLock<ArcSwap<FetchPolicy>> =
+    LazyLock::new(ArcSwap::default);
+/// Public base URL of this service
+pub(crate) static CONF_PUBLIC_URL: LazyLock<ArcSwap<Option<Arc<str>>>> =
+    LazyLock::new(ArcSwap::default);
+/// Directory of the background images of the greeting card
+pub(crate) static CONF_BACKGROUNDS_DIR: LazyLock<ArcSwap<Option<PathBuf>>> =
+    LazyLock::new(ArcSwap::default);
+/// Hours of the time of day greeting
+pub(crate) static CONF_GREETING_HOURS: LazyLock<ArcSwap<GreetingHours>> =
+    LazyLock::new(ArcSwap::default);
+/// Discourse forums, the first one is the default
+pub(crate) static CONF_FORUMS: LazyLock<ArcSwap<Vec<Arc<Forum>>>> =
+    LazyLock::new(|| ArcSwap::from_pointee(Forum::default_list()));
 
 #[derive(Debug, Parser, Serialize, Deserialize)]
 #[command(version, about, long_about = None)]
 pub(crate) struct Config {
+    #[command(subcommand)]
+    #[serde(skip)]
+    /// Subcommand to run instead of the server
+    pub command: Option<Command>,
+
+    #[arg(short, long, default_value = "./config.json")]
+    #[serde(skip)]
+    /// Path of the config file
+    ///
+    /// If the file exists, command line arguments (except the subcommand) are
+    /// ignored.
+    pub config: PathBuf,
+
     #[arg(short, long, default_value = "0.0.0.0:8989")]
     /// Listen address
     pub listen: Vec<ListenAddr>,
@@ -38,7 +77,14 @@ pub(crate) struct Config {
     ///
     /// If not set, no new counters can be added and use the existing ones from
     /// the config.
-    pub access_key: Option<Arc<String>>,
+    pub access_key: Option<Secret<Arc<String>>>,
+
+    #[arg(long)]
+    #[serde(default)]
+    /// Public base URL of this service, e.g. `https://greeting.app.acfun.win`
+    ///
+    /// Required by login, see `connect_secret` of the forums.
+    pub public_url: Option<Arc<str>>,
 
     #[arg(long, default_value = "127.0.0.0/8")]
     /// CIDR Whitelist
@@ -57,29 +103,464 @@ pub(crate) struct Config {
     /// Notice: for public service, this should not be set to `0`
     /// since your server may run out of memory.
     pub max_counter: usize,
+
+    #[arg(long, default_value = "./db.sqlite3")]
+    #[serde(default = "Config::default_db_path")]
+    /// Path of the `SQLite` database
+    pub db_path: PathBuf,
+
+    #[arg(long)]
+    #[serde(default)]
+    /// Directory of the background images of the greeting card
+    ///
+    /// Images in it are registered by file name and selectable with
+    /// `bg_type=<name>`. Scanned at startup, and reloaded with
+    /// `POST /admin/backgrounds/reload`.
+    pub backgrounds_dir: Option<PathBuf>,
+
+    #[command(flatten)]
+    #[serde(default)]
+    /// Feature toggles
+    pub features: Features,
+
+    #[command(flatten)]
+    #[serde(default)]
+    /// Defaults when query parameters are missing
+    pub defaults: Defaults,
+
+    #[arg(skip = Forum::default_list())]
+    #[serde(default = "Forum::default_list")]
+    /// Discourse forums for the card, the first one is the default
+    ///
+    /// Only configurable via config file, default to `linux.do` only.
+    pub forums: Vec<Arc<Forum>>,
+
+    #[arg(skip)]
+    #[serde(default)]
+    /// Which Discourse users may be fetched from upstream on cache miss
+    ///
+    /// Only configurable via config file, default to `claimed_counter`.
+    pub fetch_policy: FetchPolicy,
+
+    #[arg(skip)]
+    #[serde(default)]
+    /// The hour each period of the time of day greeting on the greeting card
+    /// starts at
+    ///
+    /// Only configurable via config file, default to 5 (morning), 11 (noon),
+    /// 13 (afternoon), 18 (evening) and 23 (late night).
+    pub greeting_hours: GreetingHours,
+}
+
+#[derive(Debug, Clone, Args, Serialize, Deserialize)]
+#[serde(default)]
+/// Feature toggles, all enabled by default
+pub(crate) struct Features {
+    #[arg(long = "feature-greeting", default_value_t = true, action = ArgAction::Set)]
+    /// Enable the greeting card routes (`/greeting`)
+    pub greeting: bool,
+
+    #[arg(long = "feature-moe-counter", default_value_t = true, action = ArgAction::Set)]
+    /// Enable the moe counter routes (`/moe-counter`) and `type=moe-counter`
+    pub moe_counter: bool,
+
+    #[arg(long = "feature-linux-do-card", default_value_t = true, action = ArgAction::Set)]
+    /// Enable the linux.do card routes (`/linux-do-card`) and
+    /// `type=linux-do-card`
+    pub linux_do_card: bool,
+
+    #[arg(long = "feature-counter-creation", default_value_t = true, action = ArgAction::Set)]
+    /// Allow creating new counters (`access_key` or CIDR whitelist is still
+    /// required)
+    pub counter_creation: bool,
+}
+
+impl Default for Features {
+    fn default() -> Self {
+        Self {
+            greeting: true,
+            moe_counter: true,
+            linux_do_card: true,
+            counter_creation: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Args, Serialize, Deserialize)]
+#[serde(default)]
+/// Defaults when query parameters are missing
+pub(crate) struct Defaults {
+    #[arg(long = "default-timezone", default_value = "Asia/Shanghai")]
+    /// Default timezone, see `chrono-tz` for available values
+    pub timezone: Tz,
+
+    #[arg(long = "default-theme", default_value = "moebooru")]
+    /// Default moe counter theme
+    pub theme: Arc<str>,
+
+    #[arg(long = "default-padding", default_value_t = 7)]
+    /// Default moe counter number length, range: 1-20
+    pub padding: u8,
+
+    #[arg(long = "default-scale", default_value_t = 1.0)]
+    /// Default moe counter scale, range: 0.2-2.0
+    pub scale: f32,
+
+    #[arg(long = "default-darkmode")]
+    /// Default moe counter dark mode, auto if not set
+    pub darkmode: Option<bool>,
+
+    #[arg(long = "default-bg-type", default_value = "none")]
+    /// Default greeting card background type
+    pub bg_type: BgType,
+
+    #[arg(long = "default-festival", default_value_t = true, action = ArgAction::Set)]
+    /// Whether the greeting card shows the upcoming traditional festival or
+    /// solar term by default
+    pub festival: bool,
+
+    #[arg(
+        id = "default_greeting",
+        long = "default-greeting",
+        default_value_t = true,
+        action = ArgAction::Set
+    )]
+    /// Whether the greeting card shows the time of day greeting by default
+    pub greeting: bool,
+}
+
+impl Default for Defaults {
+    fn default() -> Self {
+        Self {
+            timezone: Tz::Asia__Shanghai,
+            theme: Arc::from("moebooru"),
+            padding: 7,
+            scale: 1.0,
+            darkmode: None,
+            bg_type: BgType::None,
+            festival: true,
+            greeting: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Subcommand)]
+/// Subcommands
+pub(crate) enum Command {
+    /// Validate the config, print the effective one and exit
+    ///
+    /// Exits with non-zero status code if any error is found.
+    CheckConfig,
+
+    /// Inspect or manage the card cache of a running instance
+    ///
+    /// Requests the admin endpoints, with `access_key` from the config.
+    Cache {
+        #[arg(long)]
+        /// Base URL of the running instance, e.g. `http://127.0.0.1:8989`
+        ///
+        /// Default to the first listen address.
+        server: Option<String>,
+
+        #[command(subcommand)]
+        /// What to do
+        action: CacheAction,
+    },
+}
+
+#[derive(Debug, Clone, Subcommand)]
+/// Subcommands of [`Command::Cache`]
+pub(crate) enum CacheAction {
+    /// List cached, negative cached and in-flight users
+    List,
+
+    /// Show the raw cached data of a user
+    Show(CacheTarget),
+
+    /// Purge everything cached of a user
+    Purge(CacheTarget),
+
+    /// Refresh a user from upstream now, regardless of the fetch policy
+    Refresh(CacheTarget),
+
+    /// Clear the in-flight markers of stuck fetches
+    ClearInFlight,
+}
+
+#[derive(Debug, Clone, Args)]
+/// User of [`CacheAction`]
+pub(crate) struct CacheTarget {
+    /// Username
+    pub user: String,
+
+    #[arg(short, long)]
+    /// Forum name, default to the first one of the config
+    pub forum: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Discourse forum
+pub(crate) struct Forum {
+    /// Forum name, used in `forum=` and `/discourse-card/{forum}/{user}`
+    pub name: Arc<str>,
+
+    /// Base URL, e.g. `https://linux.do`
+    pub base_url: Arc<str>,
+
+    #[serde(default)]
+    /// Upstream rate limit
+    pub rate_limit: RateLimit,
+
+    #[serde(default)]
+    /// Card branding
+    pub branding: ForumBranding,
+
+    #[serde(default = "Forum::default_avatar_size")]
+    /// Avatar size (px) to fetch, `{size}` in Discourse's `avatar_template`
+    pub avatar_size: u16,
+
+    #[serde(default)]
+    /// Other hosts avatars may be fetched from (e.g. the CDN), besides the
+    /// host of `base_url`
+    pub avatar_hosts: Vec<Arc<str>>,
+
+    #[serde(default)]
+    /// Discourse Connect secret, enables login with this forum
+    ///
+    /// The forum should have `enable_discourse_connect_provider` on, with the
+    /// same secret in `discourse_connect_provider_secrets` for this service.
+    pub connect_secret: Option<Secret<Arc<String>>>,
+
+    #[serde(default)]
+    /// Replay the recorded responses in this file instead of requesting
+    /// `base_url`, for development and tests.
+    pub fixtures: Option<PathBuf>,
+
+    #[serde(default)]
+    /// Users on the leaderboard card, always fetched regardless of the fetch
+    /// policy.
+    pub leaderboard: Vec<Arc<str>>,
+}
+
+impl Forum {
+    /// The builtin forum list, `linux.do` only.
+    pub(crate) fn default_list() -> Vec<Arc<Self>> {
+        vec![Arc::new(Self {
+            name: Arc::from("linux-do"),
+            base_url: Arc::from("https://linux.do"),
+            rate_limit: RateLimit::default(),
+            branding: ForumBranding {
+                display_name: Arc::from("Linux.do"),
+                trust_levels: [
+                    "游客",
+                    "🚲一级新萌",
+                    "🚗二级老萌",
+                    "🚅三级大佬",
+                    "🚀站长本佬",
+                    "✨突破天际",
+                ]
+                .into_iter()
+                .map(Arc::from)
+                .collect(),
+                footer: Arc::from("Greeting SVG (originated from `linuxdo-card` by zjkal)"),
+            },
+            avatar_size: Self::default_avatar_size(),
+            avatar_hosts: Vec::new(),
+            connect_secret: None,
+            fixtures: None,
+            leaderboard: Vec::new(),
+        })]
+    }
+
+    #[inline]
+    /// Default avatar size, 96px (2x of the displayed one)
+    pub(crate) const fn default_avatar_size() -> u16 {
+        96
+    }
+
+    /// Find forum by name, or the default one if name not given.
+    pub(crate) fn find(name: Option<&str>) -> Option<Arc<Self>> {
+        let forums = CONF_FORUMS.load();
+
+        match name {
+            Some(name) => forums.iter().find(|forum| forum.name.as_ref() == name),
+            None => forums.first(),
+        }
+        .cloned()
+    }
+
+    #[inline]
+    /// Whether this is the default forum (the first one).
+    pub(crate) fn is_default(&self) -> bool {
+        CONF_FORUMS
+            .load()
+            .first()
+            .is_some_and(|default| default.name == self.name)
+    }
+
+    #[inline]
+    /// Get the full URL of the given API path (should start with `/`).
+    pub(crate) fn url(&self, path: &str) -> String {
+        let mut url = String::with_capacity(self.base_url.len() + path.len());
+        url.push_str(self.base_url.trim_end_matches('/'));
+        url.push_str(path);
+        url
+    }
+
+    #[inline]
+    /// Whether the given `Referer` is from this forum.
+    pub(crate) fn is_referer(&self, referer: &[u8]) -> bool {
+        referer
+            .strip_prefix(self.base_url.trim_end_matches('/').as_bytes())
+            .is_some_and(|rest| rest.first() == Some(&b'/'))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+/// Upstream rate limit
+pub(crate) struct RateLimit {
+    /// Max requests per second (token bucket refill rate)
+    pub per_second: f64,
+
+    /// Max burst requests (token bucket capacity)
+    pub burst: u32,
+
+    /// Base backoff (secs) for a failed user, doubled on each further failure
+    pub backoff_base: u64,
+
+    /// Max backoff (secs) for a failed user
+    pub backoff_max: u64,
+
+    /// Consecutive upstream failures to open the circuit breaker
+    pub breaker_threshold: u32,
+
+    /// How long (secs) the circuit breaker stays open
+    pub breaker_cooldown: u64,
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        Self {
+            per_second: 1.0,
+            burst: 1,
+            backoff_base: 10,
+            backoff_max: 3600,
+            breaker_threshold: 5,
+            breaker_cooldown: 60,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+/// Card branding of a forum
+pub(crate) struct ForumBranding {
+    /// Display name of the forum
+    pub display_name: Arc<str>,
+
+    /// Trust level names, indexed by trust level.
+    ///
+    /// Trust levels beyond the list use the last one.
+    pub trust_levels: Vec<Arc<str>>,
+
+    /// Footer text
+    pub footer: Arc<str>,
+}
+
+impl Default for ForumBranding {
+    fn default() -> Self {
+        Self {
+            display_name: Arc::from("Discourse"),
+            trust_levels: ["TL0", "TL1", "TL2", "TL3", "TL4"]
+                .into_iter()
+                .map(Arc::from)
+                .collect(),
+            footer: Arc::from("Greeting SVG"),
+        }
+    }
+}
+
+impl ForumBranding {
+    #[inline]
+    /// Get the name of given trust level.
+    pub(crate) fn trust_level(&self, trust_level: u8) -> &str {
+        self.trust_levels
+            .get(trust_level as usize)
+            .or(self.trust_levels.last())
+            .map_or("", AsRef::as_ref)
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+/// Which Discourse users may be fetched from upstream on cache miss.
+///
+/// Cached users are always served, and refreshed while being requested.
+pub(crate) enum FetchPolicy {
+    /// Only the users in the list (case insensitive), of any forum
+    AllowList {
+        /// Allowed usernames
+        users: Vec<Arc<str>>,
+    },
+
+    /// Any user, limited by a global fetch quota per hour
+    Any {
+        /// Max fetches per hour
+        per_hour: u32,
+    },
+
+    #[default]
+    /// Only the users with a claimed counter (the same id)
+    ClaimedCounter,
+
+    /// Like [`FetchPolicy::ClaimedCounter`], plus any user requested from the
+    /// forum itself (by `Referer`).
+    ///
+    /// `Referer` is sent by the client and can be forged, so this is opt-in
+    /// only.
+    ClaimedCounterOrReferer,
 }
 
 impl Config {
     /// Parse command line arguments, or read from config file
+    ///
+    /// Invalid arguments (and `--help`, `--version`) exit the process here,
+    /// like clap does.
     pub(crate) fn parse() -> Result<Self> {
-        let args = Config::try_parse();
+        Self::from_matches(&Config::command().get_matches())
+    }
 
-        let file = Path::new("./config.json");
+    /// See [`Config::parse`].
+    ///
+    /// Fails if the config file given by `--config` doesn't exist.
+    fn from_matches(matches: &ArgMatches) -> Result<Self> {
+        let args = Config::from_arg_matches(matches).unwrap_or_else(|e| e.exit());
+
+        let file = args.config.clone();
         if file.exists() {
-            tracing::info!("Reading config file from {}", file.to_str().unwrap());
+            tracing::info!("Reading config file from {}", file.display());
 
-            let fs = File::open(file).with_context(|| "Read config.json error")?;
-            let config: Config =
-                serde_json::from_reader(fs).with_context(|| "Parse config.json error")?;
+            let fs = File::open(&file).with_context(|| format!("Read {} error", file.display()))?;
+            let mut config: Config = serde_json::from_reader(fs)
+                .with_context(|| format!("Parse {} error", file.display()))?;
+
+            config.command = args.command;
+            config.config = file;
 
             config.update_config();
 
             return Ok(config);
         }
 
+        if matches.value_source("config") == Some(ValueSource::CommandLine) {
+            bail!("Config file {} not found", file.display());
+        }
+
         tracing::info!("Read command line arguments...");
-        args.inspect(|config| config.update_config())
-            .map_err(Into::into)
+        args.update_config();
+
+        Ok(args)
     }
 
     /// Update counter related config from given
@@ -95,9 +576,9 @@ impl Config {
         if self
             .access_key
             .as_ref()
-            .is_some_and(|access_key| !access_key.is_empty())
+            .is_some_and(|access_key| !access_key.expose().is_empty())
         {
-            let new_access_key = self.access_key.clone().unwrap();
+            let new_access_key = self.access_key.clone().unwrap().into_inner();
 
             match CONF_ACCESS_KEY.get() {
                 Some(access_key) => access_key.store(new_access_key),
@@ -110,10 +591,384 @@ impl Config {
         for &cidr in self.cidr_whitelist.iter() {
             CONF_CIDR_WHITELIST.insert(cidr);
         }
+
+        // * Update features and defaults
+        CONF_FEATURES.store(Arc::new(self.features.clone()));
+        CONF_DEFAULTS.store(Arc::new(self.defaults.clone()));
+
+        // * Update forums
+        CONF_FORUMS.store(Arc::new(self.forums.clone()));
+        CONF_PUBLIC_URL.store(Arc::new(self.public_url.clone()));
+        CONF_FETCH_POLICY.store(Arc::new(self.fetch_policy.clone()));
+
+        // * Update backgrounds directory
+        CONF_BACKGROUNDS_DIR.store(Arc::new(self.backgrounds_dir.clone()));
+
+        // * Update greeting hours
+        CONF_GREETING_HOURS.store(Arc::new(self.greeting_hours.clone()));
+    }
+
+    #[inline]
+    fn default_db_path() -> PathBuf {
+        PathBuf::from("./db.sqlite3")
+    }
+
+    /// Validate the config, returning all problems found.
+    ///
+    /// Syntax errors (invalid socket address, CIDR, etc.) have been rejected
+    /// when parsing, here we check the semantic ones.
+    pub(crate) fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+
+        // * Listen addresses
+        if self.listen.is_empty() {
+            errors.push("listen: no listen address given".to_string());
+        }
+
+        for (idx, listen) in self.listen.iter().enumerate() {
+            if self.listen[..idx].contains(listen) {
+                errors.push(format!("listen: duplicated listen address `{listen}`"));
+            }
+
+            if let ListenAddr::Unix(unix_path) = listen {
+                if cfg!(not(unix)) {
+                    errors.push(format!(
+                        "listen: unix socket `{unix_path}` is not supported on this platform"
+                    ));
+                }
+
+                if !parent_dir_exists(Path::new(unix_path)) {
+                    errors.push(format!(
+                        "listen: parent directory of unix socket `{unix_path}` does not exist"
+                    ));
+                }
+            }
+        }
+
+        // * Access key
+        if self
+            .access_key
+            .as_ref()
+            .is_some_and(|access_key| access_key.expose().is_empty())
+        {
+            errors.push("access_key: empty `access_key` is not allowed".to_string());
+        }
+
+        // * Public URL
+        if let Some(public_url) = &self.public_url {
+            if !(public_url.starts_with("https://") || public_url.starts_with("http://"))
+                || fluent_uri::Uri::parse(public_url.as_ref()).is_err()
+            {
+                errors.push(format!(
+                    "public_url: `{public_url}` is not a valid HTTP(S) URL"
+                ));
+            }
+        }
+
+        // * CIDR whitelist
+        for (idx, cidr) in self.cidr_whitelist.iter().enumerate() {
+            if self.cidr_whitelist[..idx].contains(cidr) {
+                errors.push(format!("cidr_whitelist: duplicated CIDR `{cidr}`"));
+            }
+        }
+
+        // * User ids
+        if self.user_id.iter().any(|id| id.is_empty()) {
+            errors.push("user_id: empty user id is not allowed".to_string());
+        }
+
+        // * Database
+        if self.db_path.is_dir() {
+            errors.push(format!(
+                "db_path: `{}` is a directory",
+                self.db_path.display()
+            ));
+        }
+
+        if !parent_dir_exists(&self.db_path) {
+            errors.push(format!(
+                "db_path: parent directory of `{}` does not exist",
+                self.db_path.display()
+            ));
+        }
+
+        // * Defaults
+        if !moe_counter::is_valid_theme(&self.defaults.theme) {
+            errors.push(format!(
+                "defaults.theme: unknown moe counter theme `{}`",
+                self.defaults.theme
+            ));
+        }
+
+        if !(1..=20).contains(&self.defaults.padding) {
+            errors.push(format!(
+                "defaults.padding: `{}` is out of range 1-20",
+                self.defaults.padding
+            ));
+        }
+
+        if !(0.2..=2.0).contains(&self.defaults.scale) {
+            errors.push(format!(
+                "defaults.scale: `{}` is out of range 0.2-2.0",
+                self.defaults.scale
+            ));
+        }
+
+        // * Backgrounds
+        self.validate_backgrounds(&mut errors);
+
+        // * Greeting hours
+        if !self.greeting_hours.is_valid() {
+            errors.push(
+                "greeting_hours: hours should be within 0-23 and in order of the day (morning, \
+                 noon, afternoon, evening, late night)"
+                    .to_string(),
+            );
+        }
+
+        self.validate_forums(&mut errors);
+
+        // * Fetch policy
+        match &self.fetch_policy {
+            FetchPolicy::AllowList { users } if users.is_empty() => {
+                errors.push(
+                    "fetch_policy.users: empty allow-list, no user can be fetched".to_string(),
+                );
+            }
+            FetchPolicy::Any { per_hour: 0 } => {
+                errors.push("fetch_policy.per_hour: should be at least 1".to_string());
+            }
+            _ => {}
+        }
+
+        errors
+    }
+
+    /// Validate the backgrounds directory and the default background.
+    fn validate_backgrounds(&self, errors: &mut Vec<String>) {
+        let backgrounds = match &self.backgrounds_dir {
+            Some(dir) if !dir.is_dir() => {
+                errors.push(format!(
+                    "backgrounds_dir: `{}` is not a directory",
+                    dir.display()
+                ));
+
+                return;
+            }
+            Some(dir) => match background::scan(dir) {
+                Ok(backgrounds) => backgrounds,
+                Err(e) => {
+                    errors.push(format!("backgrounds_dir: {e:#}"));
+
+                    return;
+                }
+            },
+            None => Vec::new(),
+        };
+
+        if let BgType::Custom(name) = &self.defaults.bg_type {
+            if !backgrounds
+                .iter()
+                .any(|background| background.name() == name.as_ref())
+            {
+                errors.push(format!("defaults.bg_type: unknown background `{name}`"));
+            }
+        }
+    }
+
+    /// Validate [`Forum`]s.
+    fn validate_forums(&self, errors: &mut Vec<String>) {
+        if self.forums.is_empty() {
+            errors.push("forums: at least one forum should be given".to_string());
+        }
+
+        for (idx, forum) in self.forums.iter().enumerate() {
+            if forum.name.is_empty() || forum.name.contains('/') {
+                errors.push(format!(
+                    "forums[{idx}].name: `{}` is empty or contains `/`",
+                    forum.name
+                ));
+            }
+
+            if self.forums[..idx].iter().any(|f| f.name == forum.name) {
+                errors.push(format!(
+                    "forums[{idx}].name: duplicated forum name `{}`",
+                    forum.name
+                ));
+            }
+
+            if !(forum.base_url.starts_with("https://") || forum.base_url.starts_with("http://"))
+                || fluent_uri::Uri::parse(forum.base_url.as_ref()).is_err()
+            {
+                errors.push(format!(
+                    "forums[{idx}].base_url: `{}` is not a valid HTTP(S) URL",
+                    forum.base_url
+                ));
+            }
+
+            if !(forum.rate_limit.per_second.is_finite() && forum.rate_limit.per_second > 0.0) {
+                errors.push(format!(
+                    "forums[{idx}].rate_limit.per_second: `{}` should be positive",
+                    forum.rate_limit.per_second
+                ));
+            }
+
+            if forum
+                .connect_secret
+                .as_ref()
+                .is_some_and(|secret| secret.expose().is_empty())
+            {
+                errors.push(format!(
+                    "forums[{idx}].connect_secret: empty secret is not allowed"
+                ));
+            }
+
+            if forum.connect_secret.is_some() && self.public_url.is_none() {
+                errors.push(format!(
+                    "forums[{idx}].connect_secret: `public_url` is required by login"
+                ));
+            }
+
+            if let Some(fixtures) = forum.fixtures.as_ref().filter(|path| !path.is_file()) {
+                errors.push(format!(
+                    "forums[{idx}].fixtures: `{}` is not a file",
+                    fixtures.display()
+                ));
+            }
+
+            if forum.leaderboard.len() > 100 {
+                errors.push(format!(
+                    "forums[{idx}].leaderboard: at most 100 users, got {}",
+                    forum.leaderboard.len()
+                ));
+            }
+
+            if forum.leaderboard.iter().any(|user| user.is_empty()) {
+                errors.push(format!(
+                    "forums[{idx}].leaderboard: empty username is not allowed"
+                ));
+            }
+
+            if forum.rate_limit.burst == 0 {
+                errors.push(format!(
+                    "forums[{idx}].rate_limit.burst: should be at least 1"
+                ));
+            }
+
+            if forum.rate_limit.backoff_base > forum.rate_limit.backoff_max {
+                errors.push(format!(
+                    "forums[{idx}].rate_limit.backoff_base: `{}` is greater than backoff_max `{}`",
+                    forum.rate_limit.backoff_base, forum.rate_limit.backoff_max
+                ));
+            }
+
+            if forum.rate_limit.breaker_threshold == 0 {
+                errors.push(format!(
+                    "forums[{idx}].rate_limit.breaker_threshold: should be at least 1"
+                ));
+            }
+
+            if !(16..=1024).contains(&forum.avatar_size) {
+                errors.push(format!(
+                    "forums[{idx}].avatar_size: `{}` should be within 16..=1024",
+                    forum.avatar_size
+                ));
+            }
+
+            if forum.branding.trust_levels.is_empty() {
+                errors.push(format!(
+                    "forums[{idx}].branding.trust_levels: at least one name should be given"
+                ));
+            }
+        }
+    }
+
+    /// `check-config` subcommand: validate the config and print the effective
+    /// one (with secrets redacted).
+    pub(crate) fn check(&self) -> Result<()> {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(self).context("Serialize config error")?
+        );
+
+        let errors = self.validate();
+
+        if errors.is_empty() {
+            eprintln!("Config `{}` is OK", self.config.display());
+
+            return Ok(());
+        }
+
+        for error in &errors {
+            eprintln!("[ERROR] {error}");
+        }
+
+        bail!(
+            "Config `{}` is invalid, {} error(s) found",
+            self.config.display(),
+            errors.len()
+        )
     }
 }
 
-#[derive(Debug, Clone)]
+#[inline]
+/// Whether the parent directory of the given path exists.
+///
+/// Relative path without any parent is considered OK.
+fn parent_dir_exists(path: &Path) -> bool {
+    path.parent()
+        .is_none_or(|parent| parent.as_os_str().is_empty() || parent.is_dir())
+}
+
+#[derive(Clone, Default, PartialEq, Eq)]
+/// Sensitive config value, redacted in [`Debug`] and serialized output.
+pub(crate) struct Secret<T>(T);
+
+impl<T> Secret<T> {
+    #[inline]
+    /// Get the inner value.
+    pub(crate) const fn expose(&self) -> &T {
+        &self.0
+    }
+
+    #[inline]
+    /// Consume and get the inner value.
+    pub(crate) fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> fmt::Debug for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[REDACTED]")
+    }
+}
+
+impl<T> Serialize for Secret<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        "[REDACTED]".serialize(serializer)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Secret<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        T::deserialize(deserializer).map(Self)
+    }
+}
+
+impl<T: From<String>> From<String> for Secret<T> {
+    fn from(value: String) -> Self {
+        Self(value.into())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 /// Listen address
 pub(crate) enum ListenAddr {
     /// Socket address
@@ -123,6 +978,15 @@ pub(crate) enum ListenAddr {
     Unix(String),
 }
 
+impl fmt::Display for ListenAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ListenAddr::SocketAddr(addr) => addr.fmt(f),
+            ListenAddr::Unix(path) => write!(f, "unix:{path}"),
+        }
+    }
+}
+
 impl Serialize for ListenAddr {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -158,3 +1022,13 @@ impl FromStr for ListenAddr {
             .map_err(Into::into)
     }
 }
+
+#[test]
+fn test_cli() {
+    Config::command().debug_assert();
+
+    let matches = Config::command()
+        .try_get_matches_from(["greeting-svg", "--config", "/nonexistent/config.json"])
+        .unwrap();
+    Config::from_matches(&matches).unwrap_err();
+}
@@ -1,7 +1,5 @@
 //! Counter implementation
 
-mod db;
-
 use std::{
     borrow::Cow,
     net::IpAddr,
@@ -16,7 +14,11 @@ use axum::http::StatusCode;
 use dashmap::DashMap;
 use tokio::sync::mpsc;
 
-use crate::{config::CONF_MAX_COUNTERS, utils::auth};
+use crate::{
+    config::{CONF_FEATURES, CONF_MAX_COUNTERS},
+    db,
+    utils::auth,
+};
 
 // === Static variables ===
 
@@ -39,7 +41,7 @@ impl Counter {
     pub(crate) async fn init(config: &crate::config::Config) {
         // Persistent storage
         // No need to log here when error occurs
-        let _ = db::Persistent::init()
+        let _ = db::Persistent::init(&config.db_path)
             .await
             .map(|tx| DB_PERSISTENT_TX.set(tx));
 
@@ -47,7 +49,7 @@ impl Counter {
     }
 
     /// Insert counters into [COUNTERS].
-    pub(super) fn insert_all(counters: Vec<(Arc<str>, u64)>) {
+    pub(crate) fn insert_all(counters: Vec<(Arc<str>, u64)>) {
         use rayon::prelude::*;
         counters.into_par_iter().for_each(|(id, count)| {
             tracing::debug!("Inserting counter {} with count {}", id, count);
@@ -85,6 +87,8 @@ impl Counter {
         if current_count.is_some() {
             // Save to database.
             Self::persist_data_tx(id, current_count).await;
+        } else if !CONF_FEATURES.load().counter_creation {
+            tracing::debug!("Counter creation disabled, ignore [{id}]");
         } else if auth(access_key, remote_ip) {
             Self::insert_new_counter(id).await;
             return Some(1);
@@ -128,6 +132,42 @@ impl Counter {
         Ok(())
     }
 
+    #[inline]
+    /// Whether the counter of the given id exists, without counting.
+    pub(crate) fn exists(id: &str) -> bool {
+        COUNTERS.contains_key(id)
+    }
+
+    #[inline]
+    /// Claim the counter of the given id after login, creating it (starting
+    /// from 0) if it doesn't exist.
+    ///
+    /// Returns if a new counter is created.
+    pub(crate) async fn claim(id: &str) -> bool {
+        if COUNTERS.contains_key(id) {
+            return false;
+        }
+
+        if !CONF_FEATURES.load().counter_creation {
+            tracing::debug!("Counter creation disabled, ignore [{id}]");
+            return false;
+        }
+
+        if COUNTERS.len() >= CONF_MAX_COUNTERS.load(Ordering::Acquire) {
+            tracing::warn!("Too many counters, ignore claiming [{id}]");
+            return false;
+        }
+
+        let id: Arc<str> = id.into();
+
+        tracing::info!("Counter claimed for id [{}]", id);
+
+        COUNTERS.entry(id.clone()).or_insert(AtomicU64::new(0));
+        Self::persist_data_tx(id, Some(0)).await;
+
+        true
+    }
+
     #[inline]
     /// Insert a new counter
     async fn insert_new_counter(id: Arc<str>) {
@@ -0,0 +1,475 @@
+//! Database Implementation
+//!
+//! - `Sqlite`
+//! - `PostgreSQL` TODO
+
+use std::{
+    path::Path,
+    sync::{Arc, OnceLock},
+};
+
+use anyhow::{Context, Result, anyhow, bail};
+use tokio::sync::{Semaphore, mpsc};
+
+#[cfg(feature = "sqlite")]
+/// Database pool: `sqlite`
+static DB_POOL_SQLITE: OnceLock<deadpool_sqlite::Pool> = OnceLock::new();
+
+/// Write permit, `SQLite` does not like concurrent writes.
+static DB_WRITE_PERMIT: Semaphore = Semaphore::const_new(1);
+
+/// Persistent storage
+///
+/// - `sqlite`
+pub(crate) struct Persistent;
+
+/// Persisted Discourse user cache entry: forum name, username, fetch time (unix
+/// timestamp, secs) and the serialized data.
+pub(crate) type UserCacheEntry = (Arc<str>, Arc<str>, i64, String);
+
+/// Persisted card profile entry: forum name, username (lowercased) and the
+/// serialized profile.
+pub(crate) type ProfileEntry = (Arc<str>, Arc<str>, String);
+
+impl Persistent {
+    #[tracing::instrument(err)]
+    pub(crate) async fn init(db_path: &Path) -> Result<mpsc::Sender<(Arc<str>, Option<u64>)>> {
+        // init database
+        #[cfg(feature = "sqlite")]
+        SqliteImpl::init(db_path).await?;
+
+        let (tx, mut rx) = mpsc::channel(1024);
+
+        tokio::spawn(async move {
+            while let Some((id, count)) = rx.recv().await {
+                tokio::spawn(async move {
+                    let _permit = DB_WRITE_PERMIT.acquire().await.unwrap();
+
+                    match count {
+                        Some(count) => {
+                            tracing::debug!("Write to DB: {} {}", id, count);
+
+                            if let Err(e) = SqliteImpl::sqlite_write(id, count as i64).await {
+                                tracing::error!("Write to sqlite error: {}", e);
+                            }
+                        }
+                        None => {
+                            tracing::debug!("Delete from DB: {}", id);
+                            if let Err(e) = SqliteImpl::sqlite_delete(id).await {
+                                tracing::error!("Write to sqlite error: {}", e);
+                            }
+                        }
+                    }
+                });
+            }
+        });
+
+        Ok(tx)
+    }
+
+    /// Load the latest `limit` Discourse user cache entries, older ones are
+    /// deleted.
+    ///
+    /// If database is not ready, this returns nothing.
+    pub(crate) async fn user_cache_load(limit: usize) -> Result<Vec<UserCacheEntry>> {
+        #[cfg(feature = "sqlite")]
+        if DB_POOL_SQLITE.get().is_some() {
+            let _permit = DB_WRITE_PERMIT.acquire().await?;
+
+            return SqliteImpl::sqlite_user_cache_load(limit as i64).await;
+        }
+
+        let _ = limit;
+
+        Ok(Vec::new())
+    }
+
+    /// Write a Discourse user cache entry.
+    ///
+    /// If database is not ready, this will be actually a no-op
+    pub(crate) async fn user_cache_write(entry: UserCacheEntry) {
+        #[cfg(feature = "sqlite")]
+        if DB_POOL_SQLITE.get().is_some() {
+            let _permit = DB_WRITE_PERMIT.acquire().await.unwrap();
+
+            tracing::debug!("Write user cache to DB: {}/{}", entry.0, entry.1);
+
+            if let Err(e) = SqliteImpl::sqlite_user_cache_write(entry).await {
+                tracing::error!("Write to sqlite error: {}", e);
+            }
+
+            return;
+        }
+
+        let _ = entry;
+    }
+
+    /// Load all card profiles.
+    ///
+    /// If database is not ready, this returns nothing.
+    pub(crate) async fn profile_load_all() -> Result<Vec<ProfileEntry>> {
+        #[cfg(feature = "sqlite")]
+        if DB_POOL_SQLITE.get().is_some() {
+            return SqliteImpl::sqlite_profile_load_all().await;
+        }
+
+        Ok(Vec::new())
+    }
+
+    /// Write a card profile.
+    ///
+    /// If database is not ready, this will be actually a no-op
+    pub(crate) async fn profile_write(entry: ProfileEntry) {
+        #[cfg(feature = "sqlite")]
+        if DB_POOL_SQLITE.get().is_some() {
+            let _permit = DB_WRITE_PERMIT.acquire().await.unwrap();
+
+            tracing::debug!("Write profile to DB: {}/{}", entry.0, entry.1);
+
+            if let Err(e) = SqliteImpl::sqlite_profile_write(entry).await {
+                tracing::error!("Write to sqlite error: {}", e);
+            }
+
+            return;
+        }
+
+        let _ = entry;
+    }
+
+    /// Delete a Discourse user cache entry.
+    ///
+    /// If database is not ready, this will be actually a no-op
+    pub(crate) async fn user_cache_delete(forum: Arc<str>, user: Arc<str>) {
+        #[cfg(feature = "sqlite")]
+        if DB_POOL_SQLITE.get().is_some() {
+            let _permit = DB_WRITE_PERMIT.acquire().await.unwrap();
+
+            tracing::debug!("Delete user cache from DB: {forum}/{user}");
+
+            if let Err(e) = SqliteImpl::sqlite_user_cache_delete(forum, user).await {
+                tracing::error!("Write to sqlite error: {}", e);
+            }
+
+            return;
+        }
+
+        let _ = (forum, user);
+    }
+}
+
+#[cfg(feature = "sqlite")]
+struct SqliteImpl;
+
+#[cfg(feature = "sqlite")]
+impl SqliteImpl {
+    /// Initialize sqlite database
+    async fn init(path: &Path) -> Result<()> {
+        let new_db = !path.exists();
+        let pool =
+            deadpool_sqlite::Config::new(path).create_pool(deadpool_sqlite::Runtime::Tokio1)?;
+
+        if new_db {
+            tracing::debug!("New database, create tables...");
+        }
+
+        // Tables added later should be created for existing database too.
+        if let Err(e) = pool
+                .get()
+                .await?
+                .interact(|conn| {
+                    conn.execute_batch(
+                        r#"CREATE TABLE IF NOT EXISTS counters ( id TEXT PRIMARY KEY, count INTERGER NOT NULL DEFAULT 0);
+                        CREATE TABLE IF NOT EXISTS discourse_users ( forum TEXT NOT NULL, user TEXT NOT NULL, fetched_at INTEGER NOT NULL, data TEXT NOT NULL, PRIMARY KEY (forum, user));
+                        CREATE TABLE IF NOT EXISTS card_profiles ( forum TEXT NOT NULL, user TEXT NOT NULL, updated_at INTEGER NOT NULL, data TEXT NOT NULL, PRIMARY KEY (forum, user));"#,
+                    )
+                })
+                .await
+            {
+                bail!("Failed to initialize database: {}", e);
+            }
+
+        DB_POOL_SQLITE
+            .set(pool)
+            .expect("SQLite DB pool cannot be initialized one more time");
+
+        tracing::info!("SQLite DB initialized");
+
+        if !new_db {
+            // Load data from DB
+            crate::counter::Counter::insert_all(SqliteImpl::sqlite_get_all().await?);
+        }
+
+        Ok(())
+    }
+
+    /// Read all counters from sqlite
+    pub(super) async fn sqlite_get_all() -> Result<Vec<(Arc<str>, u64)>> {
+        let result = DB_POOL_SQLITE
+            .get()
+            .context("SQLite DB not initialized")?
+            .get()
+            .await?
+            .interact(move |conn| -> Result<Vec<(Arc<str>, u64)>> {
+                let mut stmt = conn.prepare("SELECT * FROM counters")?;
+
+                // Terrible code, `rusqlite` is really a mess...
+                let rows = stmt.query_map([], |row| {
+                    Ok((row.get::<_, Arc<str>>(0)?, row.get::<_, i64>(1)? as u64))
+                })?;
+
+                let results = rows.filter_map(|row| row.ok()).collect();
+
+                Ok(results)
+            })
+            .await
+            .map_err(|e| anyhow!("{:#?}", e));
+
+        match result {
+            Ok(result) => result,
+            Err(e) => Err(e),
+        }
+    }
+
+    #[cfg(test)]
+    async fn sqlite_get(id: Arc<str>) -> Result<Option<u64>> {
+        let result = DB_POOL_SQLITE
+            .get()
+            .context("SQLite DB not initialized")?
+            .get()
+            .await?
+            .interact(move |conn| -> Result<Option<u64>> {
+                let mut stmt = conn.prepare("SELECT * FROM counters where id=?")?;
+
+                // Make compiler happy, do not just wrap the following in Ok
+                let result = match stmt.query_map([id], |row| row.get::<_, i64>(1))?.next() {
+                    Some(Ok(count)) => Some(count as u64),
+                    Some(Err(e)) => Err(e)?,
+                    None => None,
+                };
+
+                Ok(result)
+            })
+            .await
+            .map_err(|e| anyhow!("{:#?}", e));
+
+        match result {
+            Ok(result) => result,
+            Err(e) => Err(e),
+        }
+    }
+
+    #[inline]
+    /// Write to `SQLite`
+    async fn sqlite_write(id: Arc<str>, count: i64) -> Result<()> {
+        DB_POOL_SQLITE
+            .get()
+            .context("SQLite DB not initialized")?
+            .get()
+            .await?
+            .interact(move |conn| {
+                conn.execute(
+                    "INSERT OR REPLACE INTO counters (id, count) VALUES (?1, ?2)",
+                    (&id, count),
+                )
+                .unwrap();
+            })
+            .await
+            .map_err(|e| anyhow!("{:#?}", e))
+    }
+
+    /// Read the latest `limit` Discourse user cache entries, and delete the
+    /// older ones.
+    async fn sqlite_user_cache_load(limit: i64) -> Result<Vec<UserCacheEntry>> {
+        let result = DB_POOL_SQLITE
+            .get()
+            .context("SQLite DB not initialized")?
+            .get()
+            .await?
+            .interact(move |conn| -> Result<Vec<UserCacheEntry>> {
+                conn.execute(
+                    "DELETE FROM discourse_users WHERE rowid NOT IN (SELECT rowid FROM \
+                     discourse_users ORDER BY fetched_at DESC LIMIT ?1)",
+                    (limit,),
+                )?;
+
+                let mut stmt = conn.prepare(
+                    "SELECT forum, user, fetched_at, data FROM discourse_users ORDER BY \
+                     fetched_at DESC",
+                )?;
+
+                let rows = stmt.query_map([], |row| {
+                    Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+                })?;
+
+                let results = rows.filter_map(|row| row.ok()).collect();
+
+                Ok(results)
+            })
+            .await
+            .map_err(|e| anyhow!("{:#?}", e));
+
+        match result {
+            Ok(result) => result,
+            Err(e) => Err(e),
+        }
+    }
+
+    #[inline]
+    /// Write Discourse user cache entry to `SQLite`
+    async fn sqlite_user_cache_write(
+        (forum, user, fetched_at, data): UserCacheEntry,
+    ) -> Result<()> {
+        DB_POOL_SQLITE
+            .get()
+            .context("SQLite DB not initialized")?
+            .get()
+            .await?
+            .interact(move |conn| {
+                conn.execute(
+                    "INSERT OR REPLACE INTO discourse_users (forum, user, fetched_at, data) \
+                     VALUES (?1, ?2, ?3, ?4)",
+                    (&forum, &user, fetched_at, &data),
+                )
+            })
+            .await
+            .map_err(|e| anyhow!("{:#?}", e))?
+            .map(|_| ())
+            .map_err(Into::into)
+    }
+
+    /// Read all card profiles from `SQLite`
+    async fn sqlite_profile_load_all() -> Result<Vec<ProfileEntry>> {
+        DB_POOL_SQLITE
+            .get()
+            .context("SQLite DB not initialized")?
+            .get()
+            .await?
+            .interact(move |conn| -> Result<Vec<ProfileEntry>> {
+                let mut stmt = conn.prepare("SELECT forum, user, data FROM card_profiles")?;
+
+                let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+
+                Ok(rows.filter_map(|row| row.ok()).collect())
+            })
+            .await
+            .map_err(|e| anyhow!("{:#?}", e))?
+    }
+
+    #[inline]
+    /// Write card profile to `SQLite`
+    async fn sqlite_profile_write((forum, user, data): ProfileEntry) -> Result<()> {
+        DB_POOL_SQLITE
+            .get()
+            .context("SQLite DB not initialized")?
+            .get()
+            .await?
+            .interact(move |conn| {
+                conn.execute(
+                    "INSERT OR REPLACE INTO card_profiles (forum, user, updated_at, data) VALUES \
+                     (?1, ?2, ?3, ?4)",
+                    (&forum, &user, chrono::Utc::now().timestamp(), &data),
+                )
+            })
+            .await
+            .map_err(|e| anyhow!("{:#?}", e))?
+            .map(|_| ())
+            .map_err(Into::into)
+    }
+
+    #[inline]
+    /// Delete Discourse user cache entry from `SQLite`
+    async fn sqlite_user_cache_delete(forum: Arc<str>, user: Arc<str>) -> Result<()> {
+        DB_POOL_SQLITE
+            .get()
+            .context("SQLite DB not initialized")?
+            .get()
+            .await?
+            .interact(move |conn| {
+                conn.execute(
+                    "DELETE FROM discourse_users WHERE forum=?1 AND user=?2",
+                    (&forum, &user),
+                )
+            })
+            .await
+            .map_err(|e| anyhow!("{:#?}", e))?
+            .map(|_| ())
+            .map_err(Into::into)
+    }
+
+    #[inline]
+    pub(super) async fn sqlite_delete(id: Arc<str>) -> Result<()> {
+        DB_POOL_SQLITE
+            .get()
+            .context("SQLite DB not initialized")?
+            .get()
+            .await?
+            .interact(move |conn| {
+                conn.execute("DELETE FROM counters WHERE id=?", (id,))
+                    .unwrap();
+            })
+            .await
+            .map_err(|e| anyhow!("{:#?}", e))
+    }
+}
+
+#[tokio::test]
+async fn test_sqlite() {
+    macro_toolset::init_tracing_simple!();
+
+    let _ = Persistent::init(Path::new("./db.sqlite3")).await;
+
+    SqliteImpl::sqlite_write("test_data".into(), (u64::MAX - 1) as i64)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        SqliteImpl::sqlite_get("test_data".into())
+            .await
+            .unwrap()
+            .unwrap(),
+        (u64::MAX - 1)
+    );
+
+    SqliteImpl::sqlite_get_all().await.unwrap();
+
+    SqliteImpl::sqlite_user_cache_write((
+        "test-forum".into(),
+        "test_user".into(),
+        1,
+        "{}".to_string(),
+    ))
+    .await
+    .unwrap();
+
+    assert!(
+        SqliteImpl::sqlite_user_cache_load(i64::MAX)
+            .await
+            .unwrap()
+            .iter()
+            .any(|(forum, user, ..)| forum.as_ref() == "test-forum" && user.as_ref() == "test_user")
+    );
+
+    SqliteImpl::sqlite_profile_write(("test-forum".into(), "test_user".into(), "{}".to_string()))
+        .await
+        .unwrap();
+
+    assert!(
+        SqliteImpl::sqlite_profile_load_all()
+            .await
+            .unwrap()
+            .iter()
+            .any(|(forum, user, _)| forum.as_ref() == "test-forum" && user.as_ref() == "test_user")
+    );
+
+    SqliteImpl::sqlite_user_cache_delete("test-forum".into(), "test_user".into())
+        .await
+        .unwrap();
+
+    assert!(
+        !SqliteImpl::sqlite_user_cache_load(i64::MAX)
+            .await
+            .unwrap()
+            .iter()
+            .any(|(forum, user, ..)| forum.as_ref() == "test-forum" && user.as_ref() == "test_user")
+    );
+}
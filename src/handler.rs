@@ -1,19 +1,25 @@
 //! Request Handlers
 
-use std::{borrow::Cow, net::IpAddr};
+use std::{borrow::Cow, fmt::Write, net::IpAddr};
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
 use axum::{
     body::Body,
     extract::{Path, Request},
     http::{
         HeaderName, HeaderValue, Method, StatusCode,
-        header::{CONTENT_TYPE, REFERER},
+        header::{ACCEPT_LANGUAGE, CONTENT_TYPE, LOCATION, REFERER, SET_COOKIE, VARY},
     },
     response::{IntoResponse, Response},
 };
 
-use crate::{counter::Counter, svg, utils::Queries};
+use crate::{
+    auth,
+    config::{CONF_DEFAULTS, CONF_FEATURES, CONF_FORUMS, Features, Forum},
+    counter::Counter,
+    svg,
+    utils::{self, Queries},
+};
 
 #[inline]
 #[tracing::instrument]
@@ -21,7 +27,7 @@ use crate::{counter::Counter, svg, utils::Queries};
 pub(crate) async fn axum_greeting_no_path(request: Request) -> Result<Response, StatusCode> {
     tracing::debug!("Accepted request.");
 
-    match greeting::<false, false>(None, request).await {
+    match greeting::<false, false>(None, None, request).await {
         Ok(greeting) => Ok(greeting),
         Err(error) => match error.downcast::<StatusCode>() {
             Ok(status_code) => Err(status_code),
@@ -42,7 +48,7 @@ pub(crate) async fn axum_greeting(
 ) -> Result<Response, StatusCode> {
     tracing::debug!("Accepted request.");
 
-    match greeting::<false, false>(Some(id), request).await {
+    match greeting::<false, false>(Some(id), None, request).await {
         Ok(greeting) => Ok(greeting),
         Err(error) => match error.downcast::<StatusCode>() {
             Ok(status_code) => Err(status_code),
@@ -60,7 +66,7 @@ pub(crate) async fn axum_greeting(
 pub(crate) async fn axum_moe_counter_no_path(request: Request) -> Result<Response, StatusCode> {
     tracing::debug!("Accepted request.");
 
-    match greeting::<true, false>(None, request).await {
+    match greeting::<true, false>(None, None, request).await {
         Ok(greeting) => Ok(greeting),
         Err(error) => match error.downcast::<StatusCode>() {
             Ok(status_code) => Err(status_code),
@@ -111,7 +117,7 @@ pub(crate) async fn axum_moe_counter(
 ) -> Result<Response, StatusCode> {
     tracing::debug!("Accepted request.");
 
-    match greeting::<true, false>(Some(id), request).await {
+    match greeting::<true, false>(Some(id), None, request).await {
         Ok(greeting) => Ok(greeting),
         Err(error) => match error.downcast::<StatusCode>() {
             Ok(status_code) => Err(status_code),
@@ -129,7 +135,7 @@ pub(crate) async fn axum_moe_counter(
 pub(crate) async fn axum_linux_do_card_no_path(request: Request) -> Result<Response, StatusCode> {
     tracing::debug!("Accepted request.");
 
-    match greeting::<false, true>(None, request).await {
+    match greeting::<false, true>(None, None, request).await {
         Ok(greeting) => Ok(greeting),
         Err(error) => match error.downcast::<StatusCode>() {
             Ok(status_code) => Err(status_code),
@@ -163,7 +169,24 @@ pub(crate) async fn axum_linux_do_card_index(request: Request) -> Response {
         <ul>
             <li><em>note</em> 自定义 Bio, 否则读取 Linux.do 中设定的 Bio</li>
             <li><em>timezone</em> 自定义时区, 默认为 Asia/Shanghai, 可选值参见 chrono-tz 库</li>
+            <li><em>layout</em> 布局, 可选 full (默认), compact (单行), minimal (仅头像和用户名)</li>
+            <li><em>theme</em> 配色, 可选 auto (默认, 透明背景, 跟随 prefers-color-scheme), light, dark</li>
+            <li><em>accent</em> 强调色, 十六进制颜色, 如 ff8800</li>
+            <li><em>views</em> 设为 true 时显示访问计数 (需要同名计数器已存在)</li>
+            <li><em>badges</em> 设为 true 时显示徽章行 (群组徽章和最高级的几枚徽章)</li>
+            <li><em>type</em> 设为 linux-do-heatmap 时生成活跃度热力图 (类似 GitHub), 可用 <em>weeks</em> 指定周数 (12 ~ 53, 默认 26)</li>
+            <li><em>forum</em> 论坛名称, 默认为 Linux.do, 可选值取决于服务端配置. 也可以使用 <i>/discourse-card/{论坛名称}/{用户名}</i></li>
+        </ul>
+        <h2>排行榜</h2>
+        <p>URL 格式: <i>greeting.app.acfun.win/linux-do-leaderboard</i>, 在服务端配置的用户中排名.</p>
+        <ul>
+            <li><em>sort</em> 排序依据, 可选 likes_received (默认), likes_given, days_visited, solved_count, post_count, topics_entered, posts_read_count, time_read</li>
+            <li><em>count</em> 显示人数 (1 ~ 50, 默认 10)</li>
+            <li><em>layout</em> 布局, 可选 full (默认, 含头像和最近上线), compact (单行)</li>
+            <li><em>theme</em>, <em>accent</em>, <em>forum</em> 同上</li>
         </ul>
+        <h2>对比</h2>
+        <p>URL 格式: <i>greeting.app.acfun.win/linux-do-compare?a={用户名}&amp;b={用户名}</i>, 两位用户的数据并排显示, 较高者加粗. 可选 <em>theme</em>, <em>accent</em>, <em>forum</em>, 同上.</p>
         <p>例如: https://greeting.app.acfun.win/Hantong?type=linux-do-card&amp;note=%E6%88%91%E7%9A%84%E5%8D%9A%E5%AE%A2%3A%20https%3A%2F%2Facfun.win</p>
         <h2>示例图片</h2>
         <img src="https://greeting.app.acfun.win/Hantong?type=linux-do-card&amp;note=Hi%20from%20Index%21" height="220">
@@ -186,7 +209,27 @@ pub(crate) async fn axum_linux_do_card(
 ) -> Result<Response, StatusCode> {
     tracing::debug!("Accepted request.");
 
-    match greeting::<false, true>(Some(id), request).await {
+    match greeting::<false, true>(Some(id), None, request).await {
+        Ok(greeting) => Ok(greeting),
+        Err(error) => match error.downcast::<StatusCode>() {
+            Ok(status_code) => Err(status_code),
+            Err(error) => {
+                tracing::error!("{:?}", error);
+                Err(StatusCode::BAD_REQUEST)
+            }
+        },
+    }
+}
+#[inline]
+#[tracing::instrument]
+/// Discourse card router
+pub(crate) async fn axum_discourse_card(
+    Path((forum, id)): Path<(Cow<'static, str>, Cow<'static, str>)>,
+    request: Request,
+) -> Result<Response, StatusCode> {
+    tracing::debug!("Accepted request.");
+
+    match greeting::<false, true>(Some(id), Some(forum), request).await {
         Ok(greeting) => Ok(greeting),
         Err(error) => match error.downcast::<StatusCode>() {
             Ok(status_code) => Err(status_code),
@@ -197,11 +240,659 @@ pub(crate) async fn axum_linux_do_card(
         },
     }
 }
+
+#[inline]
+#[tracing::instrument]
+/// Linux.do leaderboard router, ranking `leaderboard` of the forum.
+pub(crate) async fn axum_linux_do_leaderboard(request: Request) -> Result<Response, StatusCode> {
+    tracing::debug!("Accepted request.");
+
+    let queries = Queries::try_parse_uri(request.uri());
+
+    let Some(forum) = Forum::find(queries.get("forum").map(AsRef::as_ref))
+        .filter(|forum| !forum.leaderboard.is_empty())
+    else {
+        return Err(StatusCode::NOT_FOUND);
+    };
+
+    let content = svg::linux_do_card::leaderboard::LinuxDoLeaderboardImpl::new(forum)
+        .set_metric(queries.get("sort").and_then(|sort| sort.parse().ok()))
+        .set_count(queries.get("count").and_then(|count| count.parse().ok()))
+        .set_style(svg::linux_do_card::layout::Style::from_queries(&queries))
+        .set_locale(locale(&queries, &request))
+        .generate()
+        .await;
+
+    svg_body(content)
+}
+
+#[inline]
+#[tracing::instrument]
+/// Linux.do comparison router, `a=` and `b=` side by side.
+///
+/// Each user is fetched under the same rules as the single-user card.
+pub(crate) async fn axum_linux_do_compare(request: Request) -> Result<Response, StatusCode> {
+    tracing::debug!("Accepted request.");
+
+    let queries = Queries::try_parse_uri(request.uri());
+
+    let forum =
+        Forum::find(queries.get("forum").map(AsRef::as_ref)).ok_or(StatusCode::NOT_FOUND)?;
+
+    let user = |key| {
+        queries
+            .get(key)
+            .map(|user| user.trim_start_matches('@'))
+            .filter(|user| !user.is_empty())
+    };
+
+    let (Some(a), Some(b)) = (user("a"), user("b")) else {
+        return Err(StatusCode::BAD_REQUEST);
+    };
+
+    let from_forum = from_forum(&forum, &request);
+    let requester = |user| svg::linux_do_card::Requester {
+        claimed_counter: Counter::exists(user),
+        from_forum,
+        listed: false,
+    };
+
+    let content = svg::linux_do_card::compare::LinuxDoCompareImpl::new(
+        forum,
+        [(a, requester(a)), (b, requester(b))],
+    )
+    .set_style(svg::linux_do_card::layout::Style::from_queries(&queries))
+    .set_locale(locale(&queries, &request))
+    .generate()
+    .await;
+
+    svg_body(content)
+}
+
+#[inline]
+#[tracing::instrument(skip(request))]
+/// Metrics router, in Prometheus text format.
+///
+/// Only for requests from whitelisted CIDRs, or with the access key in
+/// `X-Access-Key`.
+pub(crate) async fn axum_metrics(request: Request) -> Result<Response, StatusCode> {
+    tracing::debug!("Accepted request.");
+
+    if !utils::auth(access_key(&request), remote_ip(&request)) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let metrics = svg::linux_do_card::cache::metrics();
+
+    let mut content = String::with_capacity(1024);
+    for (name, kind, help, value) in [
+        (
+            "linux_do_card_cached_users",
+            "gauge",
+            "Cached users",
+            metrics.cached as u64,
+        ),
+        (
+            "linux_do_card_negative_cached_users",
+            "gauge",
+            "Negative cached users",
+            metrics.negative_cached as u64,
+        ),
+        (
+            "linux_do_card_in_flight_fetches",
+            "gauge",
+            "Users being fetched",
+            metrics.in_flight as u64,
+        ),
+        (
+            "linux_do_card_active_users",
+            "gauge",
+            "Recently requested users",
+            metrics.active as u64,
+        ),
+        (
+            "linux_do_card_refresh_queue_depth",
+            "gauge",
+            "Refresh queue depth",
+            metrics.queue_depth as u64,
+        ),
+        (
+            "linux_do_card_refreshed_total",
+            "counter",
+            "Background refreshes started",
+            metrics.refreshed_total,
+        ),
+        (
+            "linux_do_card_evicted_total",
+            "counter",
+            "Idle users evicted",
+            metrics.evicted_total,
+        ),
+        (
+            "linux_do_card_refresh_lag_last_milliseconds",
+            "gauge",
+            "Refresh lag of the last background refresh",
+            metrics.refresh_lag_last_ms,
+        ),
+        (
+            "linux_do_card_refresh_lag_max_milliseconds",
+            "gauge",
+            "Max refresh lag",
+            metrics.refresh_lag_max_ms,
+        ),
+    ] {
+        let _ = writeln!(
+            content,
+            "# HELP {name} {help}\n# TYPE {name} {kind}\n{name} {value}"
+        );
+    }
+
+    Response::builder()
+        .header(
+            CONTENT_TYPE,
+            HeaderValue::from_static("text/plain; version=0.0.4"),
+        )
+        .body(Body::from(content))
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+#[inline]
+#[tracing::instrument(skip(request))]
+/// Admin router: list cached, negative cached and in-flight users of the card
+/// cache.
+///
+/// Only for requests from whitelisted CIDRs, or with the access key in
+/// `X-Access-Key` (so are the other admin routers).
+pub(crate) async fn axum_admin_cache_list(request: Request) -> Result<Response, StatusCode> {
+    tracing::debug!("Accepted request.");
+
+    admin_auth(&request)?;
+
+    json_response(&svg::linux_do_card::cache::list())
+}
+
+#[inline]
+#[tracing::instrument(skip(request))]
+/// Admin router: show the raw cached data of a user.
+pub(crate) async fn axum_admin_cache_show(
+    Path((forum, user)): Path<(String, String)>,
+    request: Request,
+) -> Result<Response, StatusCode> {
+    tracing::debug!("Accepted request.");
+
+    admin_auth(&request)?;
+
+    let forum = Forum::find(Some(&forum)).ok_or(StatusCode::NOT_FOUND)?;
+    let data = svg::linux_do_card::cache::show(&forum, &user).ok_or(StatusCode::NOT_FOUND)?;
+
+    json_body(data)
+}
+
+#[inline]
+#[tracing::instrument(skip(request))]
+/// Admin router: purge everything cached of a user.
+pub(crate) async fn axum_admin_cache_purge(
+    Path((forum, user)): Path<(String, String)>,
+    request: Request,
+) -> Result<Response, StatusCode> {
+    tracing::debug!("Accepted request.");
+
+    admin_auth(&request)?;
+
+    let forum = Forum::find(Some(&forum)).ok_or(StatusCode::NOT_FOUND)?;
+
+    if svg::linux_do_card::cache::purge(&forum, &user).await {
+        tracing::info!(forum = forum.name.as_ref(), user, "Cache purged");
+
+        Ok(StatusCode::NO_CONTENT.into_response())
+    } else {
+        Err(StatusCode::NOT_FOUND)
+    }
+}
+
+#[inline]
+#[tracing::instrument(skip(request))]
+/// Admin router: refresh a user from upstream now, responding with the
+/// refreshed data.
+pub(crate) async fn axum_admin_cache_refresh(
+    Path((forum, user)): Path<(String, String)>,
+    request: Request,
+) -> Result<Response, StatusCode> {
+    tracing::debug!("Accepted request.");
+
+    admin_auth(&request)?;
+
+    let forum = Forum::find(Some(&forum)).ok_or(StatusCode::NOT_FOUND)?;
+
+    match svg::linux_do_card::cache::refresh(&forum, &user).await {
+        Ok(Some(data)) => json_body(data),
+        Ok(None) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+        Err(svg::linux_do_card::FailureKind::NotFound) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::BAD_GATEWAY),
+    }
+}
+
+#[inline]
+#[tracing::instrument(skip(request))]
+/// Admin router: clear the in-flight markers of stuck fetches.
+pub(crate) async fn axum_admin_in_flight_clear(request: Request) -> Result<Response, StatusCode> {
+    tracing::debug!("Accepted request.");
+
+    admin_auth(&request)?;
+
+    let cleared = svg::linux_do_card::cache::clear_in_flight();
+
+    tracing::info!(cleared, "In-flight markers cleared");
+
+    json_response(&serde_json::json!({ "cleared": cleared }))
+}
+
+#[inline]
+/// Check admin auth, see [`utils::auth`].
+fn admin_auth(request: &Request) -> Result<(), StatusCode> {
+    if utils::auth(access_key(request), remote_ip(request)) {
+        Ok(())
+    } else {
+        tracing::warn!("Admin auth failed");
+
+        Err(StatusCode::FORBIDDEN)
+    }
+}
+
+#[inline]
+#[tracing::instrument]
+/// Greeting backgrounds router: list the available backgrounds.
+pub(crate) async fn axum_greeting_backgrounds() -> Result<Response, StatusCode> {
+    tracing::debug!("Accepted request.");
+
+    json_response(&svg::background::list())
+}
+
+#[inline]
+#[tracing::instrument(skip(request))]
+/// Admin router: reload the backgrounds from the background directory.
+pub(crate) async fn axum_admin_backgrounds_reload(
+    request: Request,
+) -> Result<Response, StatusCode> {
+    tracing::debug!("Accepted request.");
+
+    admin_auth(&request)?;
+
+    match tokio::task::spawn_blocking(svg::background::reload).await {
+        Ok(Ok(_)) => json_response(&svg::background::list()),
+        Ok(Err(e)) => {
+            tracing::error!("Reload backgrounds error: {e:#}");
+
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+#[inline]
+/// Serialize as JSON response.
+fn json_response(value: &impl serde::Serialize) -> Result<Response, StatusCode> {
+    serde_json::to_string_pretty(value)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+        .and_then(json_body)
+}
+
+#[inline]
+/// JSON response of the given body.
+fn json_body(body: String) -> Result<Response, StatusCode> {
+    Response::builder()
+        .header(CONTENT_TYPE, HeaderValue::from_static("application/json"))
+        .body(Body::from(body))
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+#[inline]
+/// SVG response of the given body.
+fn svg_body(body: String) -> Result<Response, StatusCode> {
+    Response::builder()
+        .header(CONTENT_TYPE, HeaderValue::from_static("image/svg+xml"))
+        .header(VARY, HeaderValue::from_static("accept-language"))
+        .body(Body::from(body))
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+#[inline]
+#[tracing::instrument]
+/// Login router, redirecting to the forum.
+pub(crate) async fn axum_auth_login(Path(forum): Path<String>) -> Result<Response, StatusCode> {
+    tracing::debug!("Accepted request.");
+
+    let forum = Forum::find(Some(&forum)).ok_or(StatusCode::NOT_FOUND)?;
+
+    match auth::login_url(&forum) {
+        Ok(url) => Ok(see_other(&url, None)),
+        Err(e @ auth::LoginError::TooManyPending) => {
+            tracing::warn!("{e}");
+            Err(StatusCode::SERVICE_UNAVAILABLE)
+        }
+        Err(e) => {
+            tracing::debug!("{e}");
+            Err(StatusCode::NOT_FOUND)
+        }
+    }
+}
+
+#[inline]
+#[tracing::instrument(skip(request))]
+/// Login callback router, from the forum.
+///
+/// For the default forum, the counter with the same id as the username is
+/// claimed, see [`Counter::claim`]. Counter ids are not per forum, so users of
+/// other forums can't claim them.
+pub(crate) async fn axum_auth_callback(
+    Path(forum): Path<String>,
+    request: Request,
+) -> Result<Response, StatusCode> {
+    tracing::debug!("Accepted request.");
+
+    let forum = Forum::find(Some(&forum)).ok_or(StatusCode::NOT_FOUND)?;
+    let queries = Queries::try_parse_uri(request.uri());
+
+    let (Some(sso), Some(sig)) = (queries.get("sso"), queries.get("sig")) else {
+        return Err(StatusCode::BAD_REQUEST);
+    };
+
+    match auth::callback(&forum, sso, sig) {
+        Ok((token, session)) => {
+            if forum.is_default() {
+                Counter::claim(&session.user).await;
+            }
+
+            Ok(see_other("/auth/profile", Some(&token)))
+        }
+        Err(auth::LoginError::NotEnabled(_)) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::warn!("Login failed: {e}");
+            Err(StatusCode::BAD_REQUEST)
+        }
+    }
+}
+
+#[inline]
+#[tracing::instrument(skip(request))]
+/// Profile router: show the card profile of the logged in user.
+pub(crate) async fn axum_auth_profile(request: Request) -> Result<Response, StatusCode> {
+    tracing::debug!("Accepted request.");
+
+    let Some((_, session)) = auth::session(request.headers()) else {
+        return Ok(login_first());
+    };
+
+    let forum = Forum::find(Some(&session.forum)).ok_or(StatusCode::NOT_FOUND)?;
+    let profile = svg::linux_do_card::profile::get(&forum, &session.user).unwrap_or_default();
+
+    let card_url = if forum.is_default() {
+        format!("/linux-do-card/{}", session.user)
+    } else {
+        format!("/discourse-card/{}/{}", forum.name, session.user)
+    };
+
+    let layout_option = |value: &str, label: &str| {
+        let selected = profile
+            .layout
+            .is_some_and(|layout| value.parse() == Ok(layout));
+
+        format!(
+            r#"<option value="{value}"{}>{label}</option>"#,
+            if selected { " selected" } else { "" }
+        )
+    };
+
+    let response = format!(
+        r#"
+<html lang="zh" data-bs-theme="auto">
+    <head>
+        <meta charset="utf-8">
+        <meta name="viewport" content="width=device-width, initial-scale=1">
+        <title>Card Profile</title>
+    </head>
+    <body>
+        <h1>{user} @ {forum}</h1>
+        <p>以下设置在对应参数缺省时生效{claimed}.</p>
+        <form method="post" action="/auth/profile">
+            <p><label>Bio (最多 {bio_max_chars} 字)<br><textarea name="bio" rows="3" cols="60">{bio}</textarea></label></p>
+            <p><label>时区 (如 Asia/Shanghai, 留空使用默认)<br><input name="timezone" value="{timezone}"></label></p>
+            <p><label>布局<br><select name="layout">{layouts}</select></label></p>
+            <p><button type="submit">保存</button></p>
+        </form>
+        <form method="post" action="/auth/logout"><button type="submit">退出登录</button></form>
+        <h2>预览</h2>
+        <img src="{card_url}" height="220">
+    </body>
+</html>
+    "#,
+        user = ammonia::clean_text(&session.user),
+        forum = ammonia::clean_text(&forum.branding.display_name),
+        claimed = if Counter::exists(&session.user) && forum.is_default() {
+            format!(
+                ", 计数器 <i>{}</i> 已认领",
+                ammonia::clean_text(&session.user)
+            )
+        } else {
+            String::new()
+        },
+        bio_max_chars = svg::linux_do_card::profile::BIO_MAX_CHARS,
+        bio = ammonia::clean_text(profile.bio.as_deref().unwrap_or_default()),
+        timezone = profile.timezone.map(|tz| tz.name()).unwrap_or_default(),
+        layouts = [
+            layout_option("", "默认"),
+            layout_option("full", "full"),
+            layout_option("compact", "compact"),
+            layout_option("minimal", "minimal"),
+        ]
+        .concat(),
+        card_url = ammonia::clean_text(&card_url),
+    );
+
+    Response::builder()
+        .header(CONTENT_TYPE, HeaderValue::from_static("text/html"))
+        .body(Body::from(response))
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+#[inline]
+#[tracing::instrument(skip(request))]
+/// Profile router: update the card profile of the logged in user, from the
+/// form.
+pub(crate) async fn axum_auth_profile_update(request: Request) -> Result<Response, StatusCode> {
+    tracing::debug!("Accepted request.");
+
+    let Some((_, session)) = auth::session(request.headers()) else {
+        return Err(StatusCode::UNAUTHORIZED);
+    };
+
+    let forum = Forum::find(Some(&session.forum)).ok_or(StatusCode::NOT_FOUND)?;
+
+    let body = axum::body::to_bytes(request.into_body(), 16 * 1024)
+        .await
+        .map_err(|_| StatusCode::PAYLOAD_TOO_LARGE)?;
+    // `application/x-www-form-urlencoded` encodes space as `+`.
+    let body = String::from_utf8_lossy(&body).replace('+', "%20");
+    let form = Queries::try_parse(&body);
+
+    let field = |name: &str| {
+        form.get(name)
+            .map(|value| value.trim())
+            .filter(|value| !value.is_empty())
+    };
+
+    let bio = field("bio");
+    if bio.is_some_and(|bio| bio.chars().count() > svg::linux_do_card::profile::BIO_MAX_CHARS) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let profile = svg::linux_do_card::profile::Profile {
+        bio: bio.map(Into::into),
+        timezone: field("timezone")
+            .map(str::parse)
+            .transpose()
+            .map_err(|_| StatusCode::BAD_REQUEST)?,
+        layout: field("layout")
+            .map(str::parse)
+            .transpose()
+            .map_err(|()| StatusCode::BAD_REQUEST)?,
+    };
+
+    svg::linux_do_card::profile::set(&forum, &session.user, profile).await;
+
+    Ok(see_other("/auth/profile", None))
+}
+
+#[inline]
+#[tracing::instrument(skip(request))]
+/// Logout router.
+pub(crate) async fn axum_auth_logout(request: Request) -> Response {
+    tracing::debug!("Accepted request.");
+
+    if let Some((token, _)) = auth::session(request.headers()) {
+        auth::logout(&token);
+    }
+
+    see_other("/linux-do-card/", Some(""))
+}
+
+#[inline]
+/// `303 See Other` response, optionally setting (or clearing, if empty) the
+/// session cookie.
+fn see_other(location: &str, session_token: Option<&str>) -> Response {
+    let mut response = Response::builder()
+        .status(StatusCode::SEE_OTHER)
+        .header(LOCATION, location);
+
+    if let Some(token) = session_token {
+        response = response.header(SET_COOKIE, auth::session_cookie(token));
+    }
+
+    response
+        .body(Body::empty())
+        .unwrap_or_else(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())
+}
+
+#[inline]
+/// Page asking the user to login first.
+fn login_first() -> Response {
+    let forums = CONF_FORUMS.load();
+
+    let links: String = forums
+        .iter()
+        .filter(|forum| forum.connect_secret.is_some())
+        .map(|forum| {
+            format!(
+                r#"<li><a href="/auth/login/{}">{}</a></li>"#,
+                ammonia::clean_text(&forum.name),
+                ammonia::clean_text(&forum.branding.display_name)
+            )
+        })
+        .collect();
+
+    let response = format!(
+        r#"
+<html lang="zh" data-bs-theme="auto">
+    <head>
+        <meta charset="utf-8">
+        <meta name="viewport" content="width=device-width, initial-scale=1">
+        <title>Login</title>
+    </head>
+    <body>
+        <h1>登录</h1>
+        <p>登录后可认领同名计数器, 并设置卡片的 Bio, 时区和布局.</p>
+        <ul>{links}</ul>
+    </body>
+</html>
+    "#
+    );
+
+    Response::builder()
+        .status(StatusCode::UNAUTHORIZED)
+        .header(CONTENT_TYPE, HeaderValue::from_static("text/html"))
+        .body(Body::from(response))
+        .unwrap_or_else(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())
+}
+
 const X_FORWARDED_FOR: HeaderName = HeaderName::from_static("x-forwarded-for");
 
+const X_ACCESS_KEY: HeaderName = HeaderName::from_static("x-access-key");
+
+#[inline]
+/// Access key of the request, from `X-Access-Key`, or `access_key=` as a
+/// fallback.
+fn access_key(request: &Request) -> Option<Cow<'_, str>> {
+    request
+        .headers()
+        .get(X_ACCESS_KEY)
+        .and_then(|access_key| access_key.to_str().ok())
+        .map(Cow::Borrowed)
+        .or_else(|| Queries::try_parse_uri(request.uri()).remove("access_key"))
+}
+
+#[inline]
+/// Remote IP, from `X-Forwarded-For`
+fn remote_ip(request: &Request) -> Option<IpAddr> {
+    request
+        .headers()
+        .get(X_FORWARDED_FOR)
+        .and_then(|s| s.to_str().ok())
+        .and_then(|s| s.parse().ok())
+}
+
+#[inline]
+/// Whether the request is from the forum itself, by `Referer`.
+fn from_forum(forum: &Forum, request: &Request) -> bool {
+    request
+        .headers()
+        .get(REFERER)
+        .is_some_and(|r| forum.is_referer(r.as_bytes()))
+}
+
+#[inline]
+/// Language of the cards, by `lang=` or `Accept-Language`.
+fn locale(queries: &Queries<'_>, request: &Request) -> svg::locale::Locale {
+    svg::locale::Locale::resolve(
+        queries.get("lang").map(AsRef::as_ref),
+        request
+            .headers()
+            .get(ACCEPT_LANGUAGE)
+            .and_then(|accept_language| accept_language.to_str().ok()),
+    )
+}
+
+#[derive(Debug, Clone, Copy)]
+/// Greeting card type
+enum GreetingType {
+    /// The default greeting card
+    General,
+
+    /// Moe counter
+    MoeCounter,
+
+    /// Linux.do (or other Discourse forum) card
+    LinuxDoCard,
+
+    /// Linux.do (or other Discourse forum) activity heatmap
+    LinuxDoHeatmap,
+}
+
+impl GreetingType {
+    #[inline]
+    /// Whether this greeting type is enabled by [`Features`].
+    const fn is_enabled(self, features: &Features) -> bool {
+        match self {
+            Self::General => features.greeting,
+            Self::MoeCounter => features.moe_counter,
+            Self::LinuxDoCard | Self::LinuxDoHeatmap => features.linux_do_card,
+        }
+    }
+}
+
 #[inline]
 async fn greeting<const FORCE_MOE_COUNTER: bool, const FORCE_LINUX_DO_CARD: bool>(
     id: Option<Cow<'_, str>>,
+    forum: Option<Cow<'_, str>>,
     request: Request,
 ) -> Result<Response> {
     let queries = Queries::try_parse_uri(request.uri());
@@ -214,12 +905,41 @@ async fn greeting<const FORCE_MOE_COUNTER: bool, const FORCE_LINUX_DO_CARD: bool
         .map(|id| id.trim_start_matches("@"))
         .context("Invalid id, empty or not given.")?;
 
+    let features = CONF_FEATURES.load();
+    let defaults = CONF_DEFAULTS.load();
+
+    // * Greeting type, can be moe-counter, linux-do-card, or default one.
+    let greeting_type = match queries.get("type").map(AsRef::as_ref) {
+        Some("linux-do-card") => GreetingType::LinuxDoCard,
+        Some("linux-do-heatmap") => GreetingType::LinuxDoHeatmap,
+        _ if FORCE_LINUX_DO_CARD => GreetingType::LinuxDoCard,
+        Some("moe-counter") => GreetingType::MoeCounter,
+        _ if FORCE_MOE_COUNTER => GreetingType::MoeCounter,
+        _ => GreetingType::General,
+    };
+
+    if !greeting_type.is_enabled(&features) {
+        tracing::debug!("Greeting type {greeting_type:?} disabled.");
+        bail!(StatusCode::NOT_FOUND)
+    }
+
+    // * Discourse forum for the card, from path or `forum=`, or the default one.
+    let forum = match greeting_type {
+        GreetingType::LinuxDoCard | GreetingType::LinuxDoHeatmap => {
+            let forum_name = forum.as_deref().or(queries.get("forum").map(AsRef::as_ref));
+
+            let Some(forum) = Forum::find(forum_name) else {
+                tracing::debug!("Forum {forum_name:?} not found.");
+                bail!(StatusCode::NOT_FOUND)
+            };
+
+            Some(forum)
+        }
+        _ => None,
+    };
+
     let access_count = {
-        let remote_ip: Option<IpAddr> = request
-            .headers()
-            .get(X_FORWARDED_FOR)
-            .and_then(|s| s.to_str().ok())
-            .and_then(|s| s.parse().ok());
+        let remote_ip = remote_ip(&request);
         let access_key = queries.get("access_key");
 
         if request.method() == Method::DELETE {
@@ -237,60 +957,90 @@ async fn greeting<const FORCE_MOE_COUNTER: bool, const FORCE_LINUX_DO_CARD: bool
         }
     };
 
-    // * Greeting type, can be moe-counter, or default one.
-    let greeting_type = queries.get("type").map(AsRef::as_ref);
+    // * Card profile set by the owner, used when the query parameter is missing.
+    let profile = forum
+        .as_ref()
+        .and_then(|forum| svg::linux_do_card::profile::get(forum, id));
+
+    let tz = queries
+        .get("timezone")
+        .and_then(|tz| tz.parse().ok())
+        .or_else(|| profile.as_ref().and_then(|profile| profile.timezone))
+        .unwrap_or(defaults.timezone);
+
+    let locale = locale(&queries, &request);
+
+    // * Who is requesting the Discourse cards, see `FetchPolicy`.
+    let requester = svg::linux_do_card::Requester {
+        claimed_counter: access_count.is_some(),
+        from_forum: forum
+            .as_ref()
+            .is_some_and(|forum| from_forum(forum, &request)),
+        listed: false,
+    };
 
     let mut content = match greeting_type {
-        Some("linux-do-card") => {
-            svg::linux_do_card::LinuxDoCardImpl::new(
-                id,
-                queries
-                    .get("timezone")
-                    .and_then(|tz| tz.parse().ok())
-                    .unwrap_or(chrono_tz::Tz::Asia__Shanghai),
-                request
-                    .headers()
-                    .get(REFERER)
-                    .is_some_and(|r| r.as_bytes().starts_with(b"https://linux.do/")),
-            )
-            .set_custom_bio(queries.get("note"))
-            .await
-            .generate(access_count)
-            .await
+        GreetingType::LinuxDoCard => {
+            let forum = forum.context("Forum should have been resolved")?;
+
+            let mut style = svg::linux_do_card::layout::Style::from_queries(&queries);
+            if let Some(layout) = profile
+                .as_ref()
+                .and_then(|profile| profile.layout)
+                .filter(|_| queries.get("layout").is_none())
+            {
+                style.layout = layout;
+            }
+
+            svg::linux_do_card::LinuxDoCardImpl::new(forum, id, tz, requester)
+                .set_custom_bio(
+                    queries
+                        .get("note")
+                        .map(AsRef::as_ref)
+                        .or_else(|| profile.as_ref().and_then(|profile| profile.bio.as_deref())),
+                )
+                .await
+                .set_show_badges(queries.get("badges").is_some_and(|b| b == "true"))
+                .set_show_views(queries.get("views").is_some_and(|v| v == "true"))
+                .set_style(style)
+                .set_locale(locale)
+                .generate(access_count)
+                .await
         }
-        _ if FORCE_LINUX_DO_CARD => {
-            svg::linux_do_card::LinuxDoCardImpl::new(
-                id,
-                queries
-                    .get("timezone")
-                    .and_then(|tz| tz.parse().ok())
-                    .unwrap_or(chrono_tz::Tz::Asia__Shanghai),
-                request
-                    .headers()
-                    .get(REFERER)
-                    .is_some_and(|r| r.as_bytes().starts_with(b"https://linux.do/")),
-            )
-            .set_custom_bio(queries.get("note"))
-            .await
-            .generate(access_count)
-            .await
+        GreetingType::LinuxDoHeatmap => {
+            let forum = forum.context("Forum should have been resolved")?;
+
+            svg::linux_do_card::heatmap::LinuxDoHeatmapImpl::new(forum, id, tz, requester)
+                .set_weeks(queries.get("weeks").and_then(|weeks| weeks.parse().ok()))
+                .set_locale(locale)
+                .generate()
+                .await
+        }
+        GreetingType::MoeCounter => {
+            svg::moe_counter::MoeCounterImpl::from_queries(&queries, &defaults)
+                .generate(access_count.unwrap_or_default())
         }
-        Some("moe-counter") => svg::moe_counter::MoeCounterImpl::from_queries(&queries)
-            .generate(access_count.unwrap_or_default()),
-        _ if FORCE_MOE_COUNTER => svg::moe_counter::MoeCounterImpl::from_queries(&queries)
-            .generate(access_count.unwrap_or_default()),
-        _ => {
+        GreetingType::General => {
             svg::GeneralImpl {
-                tz: queries
-                    .get("timezone")
-                    .and_then(|tz| tz.parse().ok())
-                    .unwrap_or(chrono_tz::Tz::Asia__Shanghai),
+                tz,
                 access_count,
                 bg_type: queries
                     .get("bg_type")
                     .map(|bg_type| bg_type.parse().unwrap())
-                    .unwrap_or_default(),
+                    .unwrap_or_else(|| defaults.bg_type.clone()),
+                countdown: queries
+                    .get("countdown")
+                    .and_then(|countdown| countdown.parse().ok()),
+                label: queries.get("label"),
+                festival: queries
+                    .get("festival")
+                    .map_or(defaults.festival, |festival| festival == "true"),
+                greeting: queries
+                    .get("greeting")
+                    .map_or(defaults.greeting, |greeting| greeting == "true"),
                 note: queries.get("note"),
+                locale,
+                style: svg::style::Style::from_queries(&queries),
             }
             .generate()
             .await
@@ -303,6 +1053,7 @@ async fn greeting<const FORCE_MOE_COUNTER: bool, const FORCE_LINUX_DO_CARD: bool
 
     Response::builder()
         .header(CONTENT_TYPE, HeaderValue::from_static("image/svg+xml"))
+        .header(VARY, HeaderValue::from_static("accept-language"))
         .body(Body::from(bytes::Bytes::from(content)))
         .map_err(Into::into)
 }
@@ -1,13 +1,16 @@
 //! Simple server for generating greeting SVG
 
+mod admin;
+mod auth;
 mod config;
 mod counter;
+mod db;
 mod handler;
 mod svg;
 mod utils;
 
 use anyhow::Result;
-use axum::routing::get;
+use axum::routing::{delete, get, post};
 use macro_toolset::init_tracing_simple;
 use miku_server_timing::ServerTimingLayer;
 use tokio::{net::TcpListener, task::JoinSet};
@@ -23,37 +26,116 @@ async fn main() -> Result<()> {
 
     let config = config::Config::parse()?;
 
+    if let Some(command) = &config.command {
+        return match command {
+            config::Command::CheckConfig => config.check(),
+            config::Command::Cache { server, action } => {
+                admin::run(&config, server.as_deref(), action).await
+            }
+        };
+    }
+
     tracing::info!("{:#?}", config);
 
+    let errors = config.validate();
+    if !errors.is_empty() {
+        for error in &errors {
+            tracing::error!("Invalid config: {error}");
+        }
+
+        anyhow::bail!(
+            "Config is invalid, {} error(s) found, see `check-config`",
+            errors.len()
+        );
+    }
+
     counter::Counter::init(&config).await;
+    svg::linux_do_card::init().await;
+
+    if let Err(e) = svg::background::reload() {
+        tracing::error!("Load backgrounds error: {e:#}");
+    }
+
+    let mut service = axum::Router::new();
+
+    if config.features.greeting {
+        service = service
+            .route(
+                "/greeting",
+                get(handler::axum_greeting_no_path).delete(handler::axum_greeting_no_path),
+            )
+            .route(
+                "/greeting-backgrounds",
+                get(handler::axum_greeting_backgrounds),
+            )
+            .route(
+                "/admin/backgrounds/reload",
+                post(handler::axum_admin_backgrounds_reload),
+            )
+            .route(
+                "/greeting/{id}",
+                get(handler::axum_greeting).delete(handler::axum_greeting),
+            );
+    }
+
+    if config.features.moe_counter {
+        service = service
+            .route(
+                "/moe-counter",
+                get(handler::axum_moe_counter_no_path).delete(handler::axum_moe_counter_no_path),
+            )
+            .route("/moe-counter/", get(handler::axum_moe_counter_index))
+            .route(
+                "/moe-counter/{id}",
+                get(handler::axum_moe_counter).delete(handler::axum_moe_counter),
+            );
+    }
+
+    if config.features.linux_do_card {
+        service = service
+            .route(
+                "/linux-do-card",
+                get(handler::axum_linux_do_card_no_path)
+                    .delete(handler::axum_linux_do_card_no_path),
+            )
+            .route("/linux-do-card/", get(handler::axum_linux_do_card_index))
+            .route(
+                "/linux-do-leaderboard",
+                get(handler::axum_linux_do_leaderboard),
+            )
+            .route("/linux-do-compare", get(handler::axum_linux_do_compare))
+            .route(
+                "/linux-do-card/{id}",
+                get(handler::axum_linux_do_card).delete(handler::axum_linux_do_card),
+            )
+            .route(
+                "/discourse-card/{forum}/{id}",
+                get(handler::axum_discourse_card).delete(handler::axum_discourse_card),
+            )
+            .route("/metrics", get(handler::axum_metrics))
+            .route("/admin/cache", get(handler::axum_admin_cache_list))
+            .route(
+                "/admin/cache/{forum}/{user}",
+                get(handler::axum_admin_cache_show).delete(handler::axum_admin_cache_purge),
+            )
+            .route(
+                "/admin/cache/{forum}/{user}/refresh",
+                post(handler::axum_admin_cache_refresh),
+            )
+            .route(
+                "/admin/in-flight",
+                delete(handler::axum_admin_in_flight_clear),
+            )
+            .route("/auth/login/{forum}", get(handler::axum_auth_login))
+            .route("/auth/callback/{forum}", get(handler::axum_auth_callback))
+            .route(
+                "/auth/profile",
+                get(handler::axum_auth_profile).post(handler::axum_auth_profile_update),
+            )
+            .route("/auth/logout", post(handler::axum_auth_logout));
+    }
 
-    let service = axum::Router::new()
-        .route(
-            "/greeting",
-            get(handler::axum_greeting_no_path).delete(handler::axum_greeting_no_path),
-        )
-        .route(
-            "/greeting/{id}",
-            get(handler::axum_greeting).delete(handler::axum_greeting),
-        )
-        .route(
-            "/moe-counter",
-            get(handler::axum_moe_counter_no_path).delete(handler::axum_moe_counter_no_path),
-        )
-        .route("/moe-counter/", get(handler::axum_moe_counter_index))
-        .route(
-            "/moe-counter/{id}",
-            get(handler::axum_moe_counter).delete(handler::axum_moe_counter),
-        )
-        .route(
-            "/linux-do-card",
-            get(handler::axum_linux_do_card_no_path).delete(handler::axum_linux_do_card_no_path),
-        )
-        .route("/linux-do-card/", get(handler::axum_linux_do_card_index))
-        .route(
-            "/linux-do-card/{id}",
-            get(handler::axum_linux_do_card).delete(handler::axum_linux_do_card),
-        )
+    let service = service
         .layer(CompressionLayer::new())
         .layer(ServerTimingLayer::new(env!("CARGO_PKG_NAME")).with_description(utils::VERSION))
         .fallback(handler::not_found);
@@ -1,15 +1,36 @@
+pub(crate) mod background;
+mod color;
+mod countdown;
+mod festival;
 pub(crate) mod linux_do_card;
+pub(crate) mod locale;
+mod lunar;
 pub(crate) mod moe_counter;
+mod solar_term;
+pub(crate) mod style;
+pub(crate) mod time_of_day;
 
-use std::{borrow::Cow, convert::Infallible, str::FromStr};
+use std::{borrow::Cow, convert::Infallible, str::FromStr, sync::Arc};
 
-use chrono::{Datelike, TimeZone, Utc, Weekday};
+use chrono::{Datelike, Timelike, Utc};
 use chrono_tz::Tz;
-use macro_toolset::{str_concat, string::StringExtT};
+use macro_toolset::str_concat;
+use serde::{Deserialize, Serialize};
 
-use crate::utils::ammonia::get_filterd_note;
+pub(crate) use self::countdown::Countdown;
+use self::{
+    countdown::{Remaining, Unit},
+    locale::{Locale, fill},
+    style::Style,
+};
+use crate::{config::CONF_GREETING_HOURS, utils::ammonia::get_filterd_note};
 
-#[derive(Debug, Clone, Copy, Default)]
+/// Max characters of the countdown label
+const MAX_LABEL_CHARS: usize = 16;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// Background of the general card, see [`background`] for the registered
+/// ones.
 pub(crate) enum BgType {
     /// Count down to Lunar New Year
     LunarNewYear,
@@ -17,6 +38,9 @@ pub(crate) enum BgType {
     #[default]
     /// General background
     None,
+
+    /// Registered background of the given name
+    Custom(Arc<str>),
 }
 
 impl FromStr for BgType {
@@ -25,25 +49,40 @@ impl FromStr for BgType {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         Ok(match s {
             "lunar_new_year" => BgType::LunarNewYear,
-            _ => BgType::None,
+            "none" | "" => BgType::None,
+            name => BgType::Custom(Arc::from(name)),
         })
     }
 }
 
+impl Serialize for BgType {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.name().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for BgType {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = Cow::<'de, str>::deserialize(deserializer)?;
+        let Ok(bg_type) = s.parse();
+        Ok(bg_type)
+    }
+}
+
 impl BgType {
     #[inline]
-    fn svg_content(&self) -> impl StringExtT + use<> {
+    /// Name of the background, used in `bg_type=`
+    pub(crate) fn name(&self) -> &str {
         match self {
-            BgType::None => (
-                r#"<g id="image"><line class="line" y1="20" y2="170" x1="300.5" x2="300.5"/><image class="bg" href=""#,
-                include_str!("../assets/image/marisa-kirisame.png.data"),
-                r#"" transform="translate(300.5, 32) scale(0.5)"/></g>"#,
-            ),
-            BgType::LunarNewYear => (
-                r#"<g id="background"><image class="bg" href=""#,
-                include_str!("../assets/image/new-year.jpeg.data"),
-                r#"" transform="translate(0, 0) scale(0.25)"/></g>"#,
-            ),
+            BgType::LunarNewYear => "lunar_new_year",
+            BgType::None => "none",
+            BgType::Custom(name) => name,
         }
     }
 }
@@ -60,59 +99,114 @@ pub(crate) struct GeneralImpl<'g> {
     /// Count down type
     pub bg_type: BgType,
 
+    /// Custom countdown target, instead of the new year
+    pub countdown: Option<Countdown>,
+
+    /// Label of the countdown target, the target itself if not given
+    pub label: Option<&'g Cow<'g, str>>,
+
+    /// Show the upcoming traditional festival or solar term
+    pub festival: bool,
+
+    /// Show the time of day greeting
+    pub greeting: bool,
+
     /// Note
     pub note: Option<&'g Cow<'g, str>>,
+
+    /// Language of the card
+    pub locale: Locale,
+
+    /// Colors and typography
+    pub style: Style,
 }
 
 impl GeneralImpl<'_> {
     /// Create a new [`GeneralImpl`]
     pub(crate) async fn generate(self) -> String {
-        /// SVG static data
-        static SVG_STATIC_DATA: &str = concat!(
-            // CSS
+        /// SVG static data: CSS of the theme, followed by the custom one
+        static SVG_STATIC_CSS: &str = concat!(
             "<defs><style>",
             include_str!("../assets/theme/general/main.css"),
-            "</style></defs>",
-            // Title
-            "<title>",
-            "Cards | Jerry Zhou and Hantong Chen",
-            "</title>",
         );
 
+        /// SVG static data: title
+        static SVG_STATIC_TITLE: &str =
+            concat!("<title>", "Cards | Jerry Zhou and Hantong Chen", "</title>",);
+
         let now = Utc::now().with_timezone(&self.tz);
 
-        let now_year = now.year();
-        let now_month = now.month();
-        let now_day = now.day();
-
-        let now_weekday = match now.weekday() {
-            Weekday::Mon => "一",
-            Weekday::Tue => "二",
-            Weekday::Wed => "三",
-            Weekday::Thu => "四",
-            Weekday::Fri => "五",
-            Weekday::Sat => "六",
-            Weekday::Sun => "日",
-        };
+        let background = background::of(&self.bg_type);
+
+        let messages = self.locale.messages();
 
         let ordinal = now.ordinal();
-        let ordinal_left = match self.bg_type {
-            BgType::LunarNewYear => {
-                const LUNAR_2025_NEW_YEAR_TIMESTAMP: i64 = 1738080000;
-
-                chrono::Local
-                    .timestamp_opt(LUNAR_2025_NEW_YEAR_TIMESTAMP, 0)
-                    .unwrap()
-                    .signed_duration_since(now)
-                    .num_days()
-            }
-            BgType::None => {
-                if (now_year % 4 == 0 && now_year % 100 != 0) || now_year % 400 == 0 {
-                    366 - ordinal as i64
+        let days_left_new_year = if now.date_naive().leap_year() {
+            366 - ordinal
+        } else {
+            365 - ordinal
+        };
+        let (countdown_to, remaining) = match self.countdown {
+            Some(countdown) => {
+                let label = match self.label {
+                    Some(label) => {
+                        let label = label.chars().take(MAX_LABEL_CHARS).collect::<String>();
+
+                        get_filterd_note(&label, None, false)
+                            .await
+                            .map_or(label, |label| label.to_string())
+                    }
+                    None => String::new(),
+                };
+
+                // Nothing left after filtering, use the target itself.
+                let label = if label.trim().is_empty() {
+                    countdown.to_string()
                 } else {
-                    365 - ordinal as i64
-                }
+                    label
+                };
+
+                (Cow::Owned(label), countdown.remaining(now.naive_local()))
             }
+            None => match self.bg_type {
+                // Beyond the lunar calendar (after 2100), fall back to the solar one.
+                BgType::LunarNewYear => lunar::next_new_year(now.date_naive()).map_or(
+                    (
+                        Cow::Borrowed(messages.new_year),
+                        Remaining::Left(days_left_new_year.into(), Unit::Day),
+                    ),
+                    |new_year| {
+                        (
+                            Cow::Borrowed(messages.lunar_new_year),
+                            match (new_year - now.date_naive()).num_days() {
+                                0 => Remaining::Today,
+                                days => Remaining::Left(days.unsigned_abs(), Unit::Day),
+                            },
+                        )
+                    },
+                ),
+                BgType::None | BgType::Custom(_) => (
+                    Cow::Borrowed(messages.new_year),
+                    Remaining::Left(days_left_new_year.into(), Unit::Day),
+                ),
+            },
+        };
+
+        let festival = self
+            .festival
+            .then(|| festival::upcoming(now.date_naive()))
+            .flatten();
+
+        let greeting = self
+            .greeting
+            .then(|| CONF_GREETING_HOURS.load().period(now.hour() as u8).index());
+
+        // One more line for each of the festival and the greeting
+        let greeting_y = if festival.is_some() { 150 } else { 120 };
+        let note_y = if greeting.is_some() {
+            greeting_y + 30
+        } else {
+            greeting_y
         };
 
         let note = match self.note {
@@ -124,38 +218,83 @@ impl GeneralImpl<'_> {
         };
 
         str_concat!(
-            r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" viewBox="0 0 500 140" fr-init-rc="true">"#,
-            // Static data
-            SVG_STATIC_DATA,
-            self.bg_type.svg_content(),
+            r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" viewBox="0 0 500 "#,
+            note_y + 20,
+            r#"" fr-init-rc="true">"#,
+            // Static data and style
+            SVG_STATIC_CSS,
+            self.style.css(),
+            "</style>",
+            self.style.defs(),
+            "</defs>",
+            SVG_STATIC_TITLE,
+            self.style.background(),
+            background.svg_content(),
             // Group: detail
             r#"<g id="detail">"#,
-            r#"<text class="text" transform="translate(16 30)">欢迎您，"#,
-            self.access_count
-                .with_prefix("第 ")
-                .with_suffix(" 位访问本页面的"),
-            r#"朋友 🎉</text>"#,
-            r#"<text class="text" transform="translate(16 60)">今天是新历 "#,
-            now_year,
-            r#" 年 "#,
-            now_month,
-            r#" 月 "#,
-            now_day,
-            r#" 日，星期"#,
-            now_weekday,
+            r#"<text class="text" transform="translate(16 30)">"#,
+            match self.access_count {
+                Some(access_count) => fill(messages.welcome, &[&access_count]),
+                None => messages.welcome_anonymous.to_owned(),
+            },
+            r#"</text>"#,
+            r#"<text class="text" transform="translate(16 60)">"#,
+            fill(
+                messages.today,
+                &[
+                    &now.year(),
+                    &messages.months[now.month0() as usize],
+                    &now.day(),
+                    &messages.weekdays[now.weekday().num_days_from_monday() as usize],
+                ],
+            ),
             r#"</text>"#,
-            r#"<text class="text" transform="translate(16 90)">已经是今年的第 "#,
-            ordinal,
-            r#" 天啦，离"#,
-            match self.bg_type {
-                BgType::LunarNewYear => "农历新年",
-                BgType::None => "新历新年",
+            r#"<text class="text" transform="translate(16 90)">"#,
+            fill(messages.day_of_year, &[&ordinal]),
+            match remaining {
+                Remaining::Left(value, unit) => fill(
+                    messages.countdown_left,
+                    &[&countdown_to, &value, &unit.name(messages, value)],
+                ),
+                Remaining::Today => fill(messages.countdown_today, &[&countdown_to]),
+                Remaining::Past(value, unit) => fill(
+                    messages.countdown_past,
+                    &[&countdown_to, &value, &unit.name(messages, value)],
+                ),
             },
-            r#"还有 "#,
-            ordinal_left,
-            " 天</text>",
-            note.with_prefix(r#"<text class="text" transform="translate(16 120)">"#)
-                .with_suffix(r#"</text>"#),
+            "</text>",
+            festival.map(|festival| {
+                (
+                    r#"<text class="text" transform="translate(16 120)">"#,
+                    match festival.days_left {
+                        0 => fill(messages.festival_today, &[&festival.event.name(messages)]),
+                        days_left => fill(
+                            messages.festival_left,
+                            &[
+                                &festival.event.name(messages),
+                                &days_left,
+                                &messages.day.of(days_left),
+                            ],
+                        ),
+                    },
+                    "</text>",
+                )
+            }),
+            greeting.map(|greeting| {
+                (
+                    r#"<text class="text" transform="translate(16 "#,
+                    greeting_y,
+                    r#")">"#,
+                    messages.greetings[greeting],
+                    "</text>",
+                )
+            }),
+            note.with_prefix((
+                r#"<text class="text" transform="translate(16 "#,
+                note_y,
+                r#")">"#
+            ))
+            .with_suffix(r#"</text>"#),
             "</g>",
             // End SVG
             "</svg>"
@@ -0,0 +1,398 @@
+//! Background images of the general card
+//!
+//! Besides the built-in ones, images (PNG, JPEG, GIF, WebP or SVG) in the
+//! background directory (`backgrounds_dir` of the config) are registered by
+//! their file name without extension, selectable with `bg_type=<name>`. The
+//! placement can be set in a JSON file of the same name, e.g. `spring.json`
+//! for `spring.png`, see [`Meta`].
+
+use std::{
+    borrow::Cow,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, LazyLock},
+};
+
+use anyhow::{Context, Result, bail};
+use dashmap::DashMap;
+use macro_toolset::{
+    str_concat,
+    string::{StringExtT, b64_padding},
+};
+use serde::{Deserialize, Serialize};
+
+use super::BgType;
+use crate::config::CONF_BACKGROUNDS_DIR;
+
+/// Max size of a background image, 4 MiB
+const MAX_IMAGE_SIZE: u64 = 4 * 1024 * 1024;
+
+/// Max length of a background name
+const MAX_NAME_LEN: usize = 64;
+
+/// Backgrounds registered from the background directory
+static REGISTRY: LazyLock<DashMap<Arc<str>, Arc<Background>, foldhash::fast::RandomState>> =
+    LazyLock::new(DashMap::default);
+
+/// Built-in general background
+static BUILTIN_NONE: LazyLock<Arc<Background>> = LazyLock::new(|| {
+    Arc::new(Background::new(
+        Arc::from("none"),
+        Cow::Borrowed(include_str!("../../assets/image/marisa-kirisame.png.data")),
+        Meta::default(),
+    ))
+});
+
+/// Built-in Lunar New Year background
+static BUILTIN_LUNAR_NEW_YEAR: LazyLock<Arc<Background>> = LazyLock::new(|| {
+    Arc::new(Background::new(
+        Arc::from("lunar_new_year"),
+        Cow::Borrowed(include_str!("../../assets/image/new-year.jpeg.data")),
+        Meta {
+            placement: Placement::Full,
+            x: None,
+            y: None,
+            scale: Some(0.25),
+        },
+    ))
+});
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+/// Where the background image is placed
+pub(crate) enum Placement {
+    #[default]
+    /// On the right side, next to a divider line
+    Side,
+
+    /// Behind the text, from the top left corner
+    Full,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+/// Placement metadata of a background image, e.g.
+///
+/// ```json
+/// { "placement": "full", "x": 0, "y": 0, "scale": 0.25 }
+/// ```
+pub(crate) struct Meta {
+    /// Where the image is placed
+    pub placement: Placement,
+
+    /// Position of the image, default to `(300.5, 32)` (right to the divider
+    /// line) for [`Placement::Side`], or `(0, 0)` for [`Placement::Full`].
+    pub x: Option<f32>,
+
+    /// See `x`.
+    pub y: Option<f32>,
+
+    /// Scale of the image, default to 0.5 for [`Placement::Side`], or 1 for
+    /// [`Placement::Full`].
+    pub scale: Option<f32>,
+}
+
+impl Meta {
+    /// Position and scale of the image, with defaults applied.
+    fn transform(&self) -> (f32, f32, f32) {
+        let (x, y, scale) = match self.placement {
+            Placement::Side => (300.5, 32.0, 0.5),
+            Placement::Full => (0.0, 0.0, 1.0),
+        };
+
+        (
+            self.x.unwrap_or(x),
+            self.y.unwrap_or(y),
+            self.scale.unwrap_or(scale),
+        )
+    }
+
+    /// Check the values are sane.
+    fn validate(&self) -> Result<()> {
+        let (x, y, scale) = self.transform();
+
+        if !(x.is_finite() && y.is_finite()) {
+            bail!("Invalid position ({x}, {y})");
+        }
+
+        if !(scale.is_finite() && scale > 0.0) {
+            bail!("Invalid scale {scale}, should be positive");
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+/// A background image
+pub(crate) struct Background {
+    /// Name of the background, used in `bg_type=`
+    name: Arc<str>,
+
+    /// Data URL of the image
+    data: Cow<'static, str>,
+
+    /// Placement metadata
+    meta: Meta,
+
+    /// `transform` attribute of the image
+    transform: String,
+}
+
+impl Background {
+    fn new(name: Arc<str>, data: Cow<'static, str>, meta: Meta) -> Self {
+        let (x, y, scale) = meta.transform();
+
+        Self {
+            name,
+            data,
+            meta,
+            transform: format!("translate({x}, {y}) scale({scale})"),
+        }
+    }
+
+    /// Load a background image from the given file, with the metadata from
+    /// the JSON file of the same name if any.
+    fn load(path: &Path, mime: &str) -> Result<Self> {
+        let name = path
+            .file_stem()
+            .and_then(|name| name.to_str())
+            .filter(|name| is_valid_name(name))
+            .context("Invalid name, only ASCII letters, digits, `-` and `_` are allowed")?;
+
+        let size = fs::metadata(path)?.len();
+        if size > MAX_IMAGE_SIZE {
+            bail!("Image too large, {size} bytes");
+        }
+
+        let meta_path = path.with_extension("json");
+        let meta: Meta = if meta_path.is_file() {
+            let file = fs::File::open(&meta_path)?;
+            serde_json::from_reader(file)
+                .with_context(|| format!("Parse {} error", meta_path.display()))?
+        } else {
+            Meta::default()
+        };
+        meta.validate()?;
+
+        let data = str_concat!(
+            "data:",
+            mime,
+            ";base64,",
+            b64_padding::STANDARD::encode(fs::read(path)?)
+        );
+
+        Ok(Self::new(Arc::from(name), Cow::Owned(data), meta))
+    }
+
+    #[inline]
+    /// Name of the background
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
+    #[inline]
+    /// SVG content of the background.
+    pub(crate) fn svg_content(&self) -> impl StringExtT + use<'_> {
+        (
+            match self.meta.placement {
+                Placement::Side => {
+                    r#"<g id="image"><line class="line" y1="20" y2="170" x1="300.5" x2="300.5"/><image class="bg" href=""#
+                }
+                Placement::Full => r#"<g id="background"><image class="bg" href=""#,
+            },
+            &*self.data,
+            r#"" transform=""#,
+            &*self.transform,
+            r#""/></g>"#,
+        )
+    }
+}
+
+#[derive(Debug, Serialize)]
+/// Listed background, see [`list`]
+pub(crate) struct Entry {
+    pub name: Arc<str>,
+
+    /// Built in, or registered from the background directory
+    pub builtin: bool,
+
+    pub placement: Placement,
+
+    pub x: f32,
+
+    pub y: f32,
+
+    pub scale: f32,
+}
+
+impl From<&Background> for Entry {
+    fn from(background: &Background) -> Self {
+        let (x, y, scale) = background.meta.transform();
+
+        Self {
+            name: background.name.clone(),
+            builtin: false,
+            placement: background.meta.placement,
+            x,
+            y,
+            scale,
+        }
+    }
+}
+
+/// The background of the given type, the registered one first, or the
+/// built-in one. Unknown ones fall back to the built-in general background.
+pub(crate) fn of(bg_type: &BgType) -> Arc<Background> {
+    if let Some(background) = REGISTRY.get(bg_type.name()) {
+        return background.clone();
+    }
+
+    match bg_type {
+        BgType::LunarNewYear => BUILTIN_LUNAR_NEW_YEAR.clone(),
+        BgType::None | BgType::Custom(_) => BUILTIN_NONE.clone(),
+    }
+}
+
+/// List all backgrounds, sorted by name. Built-in ones are replaced by the
+/// registered ones of the same name.
+pub(crate) fn list() -> Vec<Entry> {
+    let mut backgrounds: Vec<Entry> = REGISTRY
+        .iter()
+        .map(|background| Entry::from(&**background))
+        .collect();
+
+    for builtin in [&*BUILTIN_NONE, &*BUILTIN_LUNAR_NEW_YEAR] {
+        if !REGISTRY.contains_key(&builtin.name) {
+            backgrounds.push(Entry {
+                builtin: true,
+                ..Entry::from(&**builtin)
+            });
+        }
+    }
+
+    backgrounds.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+    backgrounds
+}
+
+/// (Re)load backgrounds from the background directory, returning the number
+/// of registered ones.
+///
+/// Invalid images are skipped with a warning. On error, the registered ones
+/// are kept.
+pub(crate) fn reload() -> Result<usize> {
+    let backgrounds = match &**CONF_BACKGROUNDS_DIR.load() {
+        Some(dir) => scan(dir)?,
+        None => Vec::new(),
+    };
+
+    REGISTRY.retain(|name, _| {
+        backgrounds
+            .iter()
+            .any(|background| background.name == *name)
+    });
+
+    let count = backgrounds.len();
+    for background in backgrounds {
+        REGISTRY.insert(background.name.clone(), Arc::new(background));
+    }
+
+    tracing::info!("{count} background(s) registered");
+
+    Ok(count)
+}
+
+/// Scan the given directory for background images.
+pub(crate) fn scan(dir: &Path) -> Result<Vec<Background>> {
+    let mut paths = fs::read_dir(dir)
+        .with_context(|| format!("Read {} error", dir.display()))?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<PathBuf>, _>>()?;
+    paths.sort_unstable();
+
+    let mut backgrounds: Vec<Background> = Vec::new();
+
+    for path in paths {
+        let Some(mime) = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(mime_of)
+        else {
+            continue;
+        };
+
+        match Background::load(&path, mime) {
+            Ok(background) if backgrounds.iter().any(|b| b.name == background.name) => {
+                tracing::warn!(
+                    "Skip background {}: duplicated name `{}`",
+                    path.display(),
+                    background.name
+                );
+            }
+            Ok(background) => backgrounds.push(background),
+            Err(e) => tracing::warn!("Skip background {}: {e:#}", path.display()),
+        }
+    }
+
+    Ok(backgrounds)
+}
+
+#[inline]
+/// MIME type of the image of the given extension.
+fn mime_of(ext: &str) -> Option<&'static str> {
+    match &*ext.to_ascii_lowercase() {
+        "png" => Some("image/png"),
+        "jpg" | "jpeg" => Some("image/jpeg"),
+        "gif" => Some("image/gif"),
+        "webp" => Some("image/webp"),
+        "svg" => Some("image/svg+xml"),
+        _ => None,
+    }
+}
+
+#[inline]
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= MAX_NAME_LEN
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+}
+
+#[test]
+fn test_scan() {
+    let dir = std::env::temp_dir().join(format!("greeting-svg-bg-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+
+    // 1x1 transparent GIF
+    let gif = b"GIF89a\x01\x00\x01\x00\x80\x00\x00\x00\x00\x00\x00\x00\x00!\xf9\x04\x01\x00\x00\x00\x00,\x00\x00\x00\x00\x01\x00\x01\x00\x00\x02\x02D\x01\x00;";
+    fs::write(dir.join("spring.gif"), gif).unwrap();
+    fs::write(
+        dir.join("spring.json"),
+        r#"{ "placement": "full", "scale": 0.25 }"#,
+    )
+    .unwrap();
+    fs::write(dir.join("winter.PNG"), gif).unwrap();
+    fs::write(dir.join("bad name.png"), gif).unwrap();
+    fs::write(dir.join("bad-meta.png"), gif).unwrap();
+    fs::write(dir.join("bad-meta.json"), r#"{ "scale": -1 }"#).unwrap();
+    fs::write(dir.join("notes.txt"), "").unwrap();
+
+    let backgrounds = scan(&dir).unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+
+    let names: Vec<_> = backgrounds.iter().map(|b| &*b.name).collect();
+    assert_eq!(names, ["spring", "winter"]);
+
+    let spring = &backgrounds[0];
+    assert_eq!(spring.meta.placement, Placement::Full);
+    assert_eq!(spring.transform, "translate(0, 0) scale(0.25)");
+    assert!(spring.data.starts_with("data:image/gif;base64,R0lGODlh"));
+
+    let winter = &backgrounds[1];
+    assert_eq!(winter.meta.placement, Placement::Side);
+    assert_eq!(winter.transform, "translate(300.5, 32) scale(0.5)");
+    assert!(winter.data.starts_with("data:image/png;base64,"));
+}
@@ -0,0 +1,67 @@
+//! Color schemes shared by the general card and the Linux.do cards
+
+use std::str::FromStr;
+
+use macro_toolset::str_concat;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// Color scheme
+pub(crate) enum ColorScheme {
+    #[default]
+    /// Transparent background, follows `prefers-color-scheme`
+    Auto,
+
+    /// Light background
+    Light,
+
+    /// Dark background
+    Dark,
+}
+
+impl FromStr for ColorScheme {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(Self::Auto),
+            "light" => Ok(Self::Light),
+            "dark" => Ok(Self::Dark),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Colors of a scheme
+pub(crate) struct Palette {
+    pub accent: &'static str,
+    pub line: &'static str,
+    pub background: &'static str,
+}
+
+pub(crate) const LIGHT: Palette = Palette {
+    accent: "rgba(0, 140, 255, 1)",
+    line: "rgba(211, 211, 211, 1)",
+    background: "rgba(255, 255, 255, 1)",
+};
+
+pub(crate) const DARK: Palette = Palette {
+    accent: "rgba(88, 166, 255, 1)",
+    line: "rgba(48, 54, 61, 1)",
+    background: "rgba(13, 17, 23, 1)",
+};
+
+/// Parse hex color, returns `#` prefixed one.
+pub(crate) fn parse_hex_color(color: &str) -> Option<String> {
+    let color = color.strip_prefix('#').unwrap_or(color);
+
+    (matches!(color.len(), 3 | 6 | 8) && color.bytes().all(|b| b.is_ascii_hexdigit()))
+        .then(|| str_concat!("#", color))
+}
+
+#[test]
+fn test_parse_hex_color() {
+    assert_eq!(parse_hex_color("FFF").as_deref(), Some("#FFF"));
+    assert_eq!(parse_hex_color("#ff8800").as_deref(), Some("#ff8800"));
+    assert_eq!(parse_hex_color("12345"), None);
+    assert_eq!(parse_hex_color("red"), None);
+}
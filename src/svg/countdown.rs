@@ -0,0 +1,219 @@
+//! Custom countdown targets of the general card
+
+use std::{fmt, str::FromStr};
+
+use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta};
+
+use super::locale::Messages;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Countdown target, in the requested timezone
+///
+/// Parsed from `YYYY-MM-DD[THH:MM]`, or `MM-DD[THH:MM]` (also `--MM-DD`) for
+/// one recurring yearly, like birthdays.
+pub(crate) enum Countdown {
+    /// A single date, or moment if the time is given
+    Once(NaiveDate, Option<NaiveTime>),
+
+    /// Every year, February 29th falls on February 28th in common years.
+    Yearly {
+        month: u32,
+        day: u32,
+        time: Option<NaiveTime>,
+    },
+}
+
+impl FromStr for Countdown {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (date, time) = match s.split_once('T') {
+            Some((date, time)) => (
+                date,
+                Some(NaiveTime::parse_from_str(time, "%H:%M").map_err(|_| ())?),
+            ),
+            None => (s, None),
+        };
+
+        let date = date.strip_prefix("--").unwrap_or(date);
+
+        match date.len() {
+            // MM-DD
+            5 => {
+                let (month, day) = date.split_once('-').ok_or(())?;
+                let (month, day) = (month.parse().map_err(|_| ())?, day.parse().map_err(|_| ())?);
+
+                // Any leap year will do.
+                if NaiveDate::from_ymd_opt(2000, month, day).is_none() {
+                    return Err(());
+                }
+
+                Ok(Self::Yearly { month, day, time })
+            }
+            _ => Ok(Self::Once(
+                NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| ())?,
+                time,
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Countdown {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let time = match self {
+            Self::Once(date, time) => {
+                write!(f, "{}", date.format("%Y-%m-%d"))?;
+                time
+            }
+            Self::Yearly { month, day, time } => {
+                write!(f, "{month:02}-{day:02}")?;
+                time
+            }
+        };
+
+        match time {
+            Some(time) => write!(f, " {}", time.format("%H:%M")),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Unit of [`Remaining`]
+pub(crate) enum Unit {
+    Day,
+    Hour,
+    Minute,
+}
+
+impl Unit {
+    #[inline]
+    /// Name of the unit, for the given value.
+    pub(crate) const fn name(self, messages: &Messages, value: u64) -> &'static str {
+        match self {
+            Self::Day => messages.day.of(value),
+            Self::Hour => messages.hour.of(value),
+            Self::Minute => messages.minute.of(value),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// How far the target is from now
+pub(crate) enum Remaining {
+    /// Still to come
+    Left(u64, Unit),
+
+    /// Today is the day, for targets without time.
+    Today,
+
+    /// Already passed
+    Past(u64, Unit),
+}
+
+impl Countdown {
+    /// How far the target is from `now`, in local time of the requested
+    /// timezone.
+    pub(crate) fn remaining(&self, now: NaiveDateTime) -> Remaining {
+        let (date, time) = match *self {
+            Self::Once(date, time) => (date, time),
+            Self::Yearly { month, day, time } => {
+                let occurrence = |year| {
+                    NaiveDate::from_ymd_opt(year, month, day)
+                        .or_else(|| NaiveDate::from_ymd_opt(year, month, day - 1))
+                };
+
+                let upcoming = [now.year(), now.year() + 1]
+                    .into_iter()
+                    .filter_map(occurrence)
+                    .find(|date| match time {
+                        Some(time) => date.and_time(time) >= now,
+                        None => *date >= now.date(),
+                    });
+
+                let Some(date) = upcoming else {
+                    // Never, the date is checked when parsing.
+                    return Remaining::Today;
+                };
+
+                (date, time)
+            }
+        };
+
+        match time {
+            Some(time) => {
+                let delta = date.and_time(time) - now;
+
+                if delta > TimeDelta::zero() {
+                    let (value, unit) = humanize(delta);
+                    Remaining::Left(value, unit)
+                } else {
+                    let (value, unit) = humanize(-delta);
+                    Remaining::Past(value, unit)
+                }
+            }
+            None => match (date - now.date()).num_days() {
+                0 => Remaining::Today,
+                days if days > 0 => Remaining::Left(days.unsigned_abs(), Unit::Day),
+                days => Remaining::Past(days.unsigned_abs(), Unit::Day),
+            },
+        }
+    }
+}
+
+#[inline]
+/// Days, or hours / minutes if less than one day / hour.
+fn humanize(delta: TimeDelta) -> (u64, Unit) {
+    match (delta.num_days(), delta.num_hours()) {
+        (0, 0) => (delta.num_minutes().max(1).unsigned_abs(), Unit::Minute),
+        (0, hours) => (hours.unsigned_abs(), Unit::Hour),
+        (days, _) => (days.unsigned_abs(), Unit::Day),
+    }
+}
+
+#[test]
+fn test_countdown() {
+    let now = NaiveDate::from_ymd_opt(2026, 10, 19)
+        .unwrap()
+        .and_hms_opt(15, 30, 0)
+        .unwrap();
+    let remaining = |s: &str| s.parse::<Countdown>().unwrap().remaining(now);
+
+    assert_eq!(remaining("2026-12-25"), Remaining::Left(67, Unit::Day));
+    assert_eq!(remaining("2026-10-19"), Remaining::Today);
+    assert_eq!(remaining("2026-10-01"), Remaining::Past(18, Unit::Day));
+    assert_eq!(
+        remaining("2026-10-19T18:00"),
+        Remaining::Left(2, Unit::Hour)
+    );
+    assert_eq!(
+        remaining("2026-10-19T15:40"),
+        Remaining::Left(10, Unit::Minute)
+    );
+    assert_eq!(remaining("2026-10-18T15:00"), Remaining::Past(1, Unit::Day));
+
+    // Yearly, never in the past
+    assert_eq!(remaining("10-20"), Remaining::Left(1, Unit::Day));
+    assert_eq!(remaining("--10-19"), Remaining::Today);
+    assert_eq!(remaining("10-18"), Remaining::Left(364, Unit::Day));
+    assert_eq!(remaining("10-19T09:00"), Remaining::Left(364, Unit::Day));
+    // 2027 is a common year
+    assert_eq!(remaining("02-29"), Remaining::Left(132, Unit::Day));
+
+    for invalid in [
+        "",
+        "2026-13-01",
+        "02-30",
+        "10-19T25:00",
+        "2026/10/19",
+        "1-1",
+    ] {
+        assert!(invalid.parse::<Countdown>().is_err(), "{invalid}");
+    }
+
+    assert_eq!(
+        "2026-12-25T08:00".parse::<Countdown>().unwrap().to_string(),
+        "2026-12-25 08:00"
+    );
+    assert_eq!("--02-29".parse::<Countdown>().unwrap().to_string(), "02-29");
+}
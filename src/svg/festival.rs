@@ -0,0 +1,109 @@
+//! Traditional festivals and solar terms shown on the general card
+
+use chrono::{Datelike, Days, NaiveDate};
+
+use super::{locale::Messages, lunar, solar_term};
+
+/// How many days ahead to look for the next festival or solar term
+const LOOK_AHEAD_DAYS: u64 = 7;
+
+/// Traditional festivals on lunar dates, (month, day), named in
+/// [`Messages::festivals`]
+const FESTIVALS: [(u8, u8); 10] = [
+    (1, 1),   // 春节
+    (1, 15),  // 元宵节
+    (2, 2),   // 龙抬头
+    (5, 5),   // 端午节
+    (7, 7),   // 七夕节
+    (7, 15),  // 中元节
+    (8, 15),  // 中秋节
+    (9, 9),   // 重阳节
+    (12, 8),  // 腊八节
+    (12, 23), // 小年
+];
+
+/// New Year's Eve (除夕), following [`FESTIVALS`].
+const NEW_YEARS_EVE: usize = FESTIVALS.len();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Event {
+    /// Index of [`Messages::festivals`]
+    Festival(usize),
+
+    /// Index of [`Messages::solar_terms`]
+    SolarTerm(usize),
+}
+
+impl Event {
+    #[inline]
+    pub(crate) const fn name(self, messages: &Messages) -> &'static str {
+        match self {
+            Self::Festival(idx) => messages.festivals[idx],
+            Self::SolarTerm(idx) => messages.solar_terms[idx],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// A festival or solar term coming soon
+pub(crate) struct Upcoming {
+    pub event: Event,
+
+    /// Days left, 0 for today
+    pub days_left: u64,
+}
+
+/// The nearest festival or solar term within [`LOOK_AHEAD_DAYS`] since
+/// `today` (inclusive). Festivals come first when on the same day.
+pub(crate) fn upcoming(today: NaiveDate) -> Option<Upcoming> {
+    let until = today.checked_add_days(Days::new(LOOK_AHEAD_DAYS))?;
+
+    // The lunar year begins in January or February, so January may still be in
+    // the last one.
+    let festivals = [today.year() - 1, today.year()]
+        .into_iter()
+        .flat_map(|year| {
+            FESTIVALS
+                .iter()
+                .enumerate()
+                .map(move |(idx, &(month, day))| {
+                    (lunar::to_solar(year, month, day), Event::Festival(idx))
+                })
+                .chain([(lunar::new_years_eve(year), Event::Festival(NEW_YEARS_EVE))])
+        });
+
+    let solar_terms = [today.year(), today.year() + 1]
+        .into_iter()
+        .flat_map(|year| {
+            (0..solar_term::COUNT)
+                .map(move |term| (solar_term::date(year, term), Event::SolarTerm(term)))
+        });
+
+    festivals
+        .chain(solar_terms)
+        .filter_map(|(date, event)| date.map(|date| (date, event)))
+        .filter(|(date, _)| (today..=until).contains(date))
+        .min_by_key(|(date, _)| *date)
+        .map(|(date, event)| Upcoming {
+            event,
+            days_left: (date - today).num_days() as u64,
+        })
+}
+
+#[test]
+fn test_upcoming() {
+    let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+    let messages = super::locale::Locale::ZhCn.messages();
+    let upcoming = |today| upcoming(today).map(|u| (u.event.name(messages), u.days_left));
+
+    assert_eq!(upcoming(date(2025, 5, 31)), Some(("端午节", 0)));
+    assert_eq!(upcoming(date(2025, 10, 4)), Some(("中秋节", 2)));
+    assert_eq!(upcoming(date(2025, 2, 12)), Some(("元宵节", 0)));
+    assert_eq!(upcoming(date(2026, 2, 16)), Some(("除夕", 0)));
+    // 大寒 first, then 小年 of lunar 2024 on 2025-01-22
+    assert_eq!(upcoming(date(2025, 1, 19)), Some(("大寒", 1)));
+    assert_eq!(upcoming(date(2025, 1, 21)), Some(("小年", 1)));
+    assert_eq!(upcoming(date(2026, 12, 19)), Some(("冬至", 3)));
+    assert_eq!(upcoming(date(2025, 12, 29)), Some(("小寒", 7)));
+    assert_eq!(upcoming(date(2025, 7, 12)), None);
+}
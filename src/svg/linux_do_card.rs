@@ -1,10 +1,17 @@
 //! Linux.do CARD
 
-mod cache;
+pub(crate) mod cache;
+pub(crate) mod compare;
+pub(crate) mod heatmap;
+pub(crate) mod layout;
+pub(crate) mod leaderboard;
+mod limiter;
 mod model;
+mod policy;
+pub(crate) mod profile;
 mod upstream;
 
-use std::sync::{Arc, LazyLock};
+use std::{borrow::Cow, sync::Arc};
 
 use chrono_tz::Tz;
 use macro_toolset::{
@@ -12,35 +19,57 @@ use macro_toolset::{
     string::{NumStr, StringExtT},
 };
 
-use crate::utils::ammonia::get_filterd_note;
+use self::{
+    layout::{Layout, Style},
+    leaderboard::Metric,
+    policy::Rejected,
+};
+pub(crate) use self::{policy::Requester, upstream::FailureKind};
+use super::{
+    color::ColorScheme,
+    locale::{Locale, Messages, fill},
+};
+use crate::{config::Forum, utils::ammonia::get_filterd_note};
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone)]
 pub(crate) struct LinuxDoCardImpl<'i, V = &'i str> {
-    user: Option<&'i str>,
+    forum: Arc<Forum>,
+    user: &'i str,
     custom_bio: Option<V>,
     filtered_bio: Option<Arc<str>>,
     tz: Tz,
-    from_linux_do: bool,
+    requester: Requester,
+    show_badges: bool,
+    show_views: bool,
+    style: Style,
+    locale: Locale,
 }
 
 impl<'i> LinuxDoCardImpl<'i> {
-    pub(crate) const fn new(user: &'i str, tz: Tz, from_linux_do: bool) -> Self {
+    /// Create a new [`LinuxDoCardImpl`].
+    ///
+    /// `requester`: who is requesting, see [`Requester`].
+    pub(crate) const fn new(
+        forum: Arc<Forum>,
+        user: &'i str,
+        tz: Tz,
+        requester: Requester,
+    ) -> Self {
         Self {
-            user: Some(user),
+            forum,
+            user,
             custom_bio: None,
             filtered_bio: None,
             tz,
-            from_linux_do,
-        }
-    }
-
-    const fn empty() -> Self {
-        Self {
-            user: None,
-            custom_bio: None,
-            filtered_bio: None,
-            tz: Tz::Asia__Shanghai,
-            from_linux_do: false,
+            requester,
+            show_badges: false,
+            show_views: false,
+            style: Style {
+                layout: Layout::Full,
+                scheme: ColorScheme::Auto,
+                accent: None,
+            },
+            locale: Locale::ZhCn,
         }
     }
 }
@@ -50,18 +79,22 @@ where
     V: AsRef<str>,
 {
     pub(crate) async fn generate(self, count: Option<u64>) -> String {
-        static DEFAULT_EMPTY_USER_INFO: LazyLock<String> =
-            LazyLock::new(|| LinuxDoCardImpl::empty().create(&model::UserInfo::default()));
-
         cache::try_init_cache_update_queue().await;
 
-        if let Some(user) = self.user {
-            if let Some(v) = get_or_fetch(user, self.from_linux_do || count.is_some()).await {
-                return self.create(&v);
+        let views = count.filter(|_| self.show_views);
+
+        match get_or_fetch(&self.forum, self.user, self.requester).await {
+            cache::Cached::Hit(v) => self.create(&v, None, views),
+            cache::Cached::Failed(kind) => {
+                self.create(&model::UserInfo::default(), Some(kind), views)
             }
+            cache::Cached::Rejected(rejected) => self.create(
+                &model::UserInfo::default(),
+                Some(FailureKind::Rejected(rejected)),
+                views,
+            ),
+            cache::Cached::Miss => self.create(&model::UserInfo::default(), None, views),
         }
-
-        DEFAULT_EMPTY_USER_INFO.clone()
     }
 
     pub(crate) async fn set_custom_bio<NV>(self, custom_bio: Option<NV>) -> LinuxDoCardImpl<'i, NV>
@@ -75,119 +108,454 @@ where
         };
 
         LinuxDoCardImpl {
+            forum: self.forum,
             user: self.user,
             custom_bio,
             filtered_bio,
             tz: self.tz,
-            from_linux_do: self.from_linux_do,
+            requester: self.requester,
+            show_badges: self.show_badges,
+            show_views: self.show_views,
+            style: self.style,
+            locale: self.locale,
+        }
+    }
+
+    #[inline]
+    /// Set the layout and colors.
+    pub(crate) fn set_style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    #[inline]
+    /// Set the language.
+    pub(crate) fn set_locale(mut self, locale: Locale) -> Self {
+        self.locale = locale;
+        self
+    }
+
+    #[inline]
+    /// Show the visit count (of the counter with the same id) or not.
+    pub(crate) fn set_show_views(mut self, show_views: bool) -> Self {
+        self.show_views = show_views;
+        self
+    }
+
+    #[inline]
+    /// Show the badges row or not.
+    pub(crate) fn set_show_badges(mut self, show_badges: bool) -> Self {
+        self.show_badges = show_badges;
+        self
+    }
+
+    fn create(
+        self,
+        user_info: &model::UserInfo,
+        failure: Option<FailureKind>,
+        views: Option<u64>,
+    ) -> String {
+        match self.style.layout {
+            Layout::Full => self.create_full(user_info, failure, views),
+            Layout::Compact => self.create_compact(user_info, failure, views),
+            Layout::Minimal => self.create_minimal(user_info, failure, views),
         }
     }
 
-    fn create(self, user_info: &model::UserInfo) -> String {
+    fn create_full(
+        &self,
+        user_info: &model::UserInfo,
+        failure: Option<FailureKind>,
+        views: Option<u64>,
+    ) -> String {
+        let messages = self.locale.messages();
+
+        // The badges row takes 30px more.
+        let (height, footer_y) = if self.show_badges {
+            (300, 285)
+        } else {
+            (270, 250)
+        };
+
         str_concat!(
+            self.header(600, height),
+            avatar(user_info, 54, 42, 24),
+            views.map(|views| views_corner(views, messages)),
             r#"
-            <svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" viewBox="0 0 600 270" fr-init-rc="true">
-                <defs>
-                    <style>
-                        svg { background-color: rgba(0, 0, 0, 0); }
-                        #info .text { font-size: 16px; fill: rgba(0, 140, 255, 1); font-weight: lighter; }
-                        #summary .text { font-size: 16px; fill: rgba(0, 140, 255, 1); font-weight: lighter; }
-                        #edit .text { font-size: 10px; fill: rgba(0, 140, 255, 1); font-weight: lighter; }
-                    </style>
-                </defs>
                 <g id="info">
-                    <text class="text" transform="translate(30 30)">"#,
-            &user_info.user.username,
-            r#" ("#,
-            match user_info.user.trust_level {
-                0 => "游客",
-                1 => "🚲一级新萌",
-                2 => "🚗二级老萌",
-                3 => "🚅三级大佬",
-                4 => "🚀站长本佬",
-                _ => "✨突破天际",
-            },
-            r#")</text>
-                    <text class="text" transform="translate(30 60)">"#,
-            self.filtered_bio
-                .as_ref()
-                .map(AsRef::as_ref)
+                    <text class="text" transform="translate(90 30)">"#,
+            name_line(user_info, &self.forum),
+            r#"</text>
+                    <text class="text" transform="translate(90 60)">"#,
+            failure
+                .map(|failure| status(failure, messages))
+                .or(self.filtered_bio.as_ref().map(AsRef::as_ref))
                 .or(self.custom_bio.as_ref().map(AsRef::as_ref))
                 .or(user_info.user.bio_raw.as_ref().map(AsRef::as_ref))
-                .unwrap_or("小白一枚"), // BIO
+                .unwrap_or(messages.default_bio), // BIO
+            r#"</text>
+                    <text class="text" transform="translate(30 90)">"#,
+            messages.joined,
+            r#"</text>
+                    <text class="text" transform="translate(330 90)">"#,
+            messages.last_seen,
             r#"</text>
-                    <text class="text" transform="translate(30 90)">🕒注册时间</text>
-                    <text class="text" transform="translate(330 90)">🕗最近上线</text>
                     <text class="text" transform="translate(150 90)">"#,
-            cal_time_delta(user_info.user.created_at),
+            cal_time_delta(user_info.user.created_at, messages),
             r#"</text>
                     <text class="text" transform="translate(450 90)">"#,
-            cal_time_delta(user_info.user.last_seen_at),
+            cal_time_delta(user_info.user.last_seen_at, messages),
             r#"</text>
                 </g>
-                <line x1="30" y1="100" x2="570" y2="100" stroke="rgba(211, 211, 211, 1)" stroke-width="1"/>
-                <g id="summary">
-                    <text class="text" transform="translate(30 130)">🛎️访问天数</text>
-                    <text class="text" transform="translate(30 160)">⌛阅读时间</text>
-                    <text class="text" transform="translate(30 190)">📰浏览话题</text>
-                    <text class="text" transform="translate(30 220)">📑已读帖子</text>
-                    <text class="text" transform="translate(330 130)">💝已送出赞</text>
-                    <text class="text" transform="translate(330 160)">👍已收到赞</text>
-                    <text class="text" transform="translate(330 190)">📖创建帖子</text>
-                    <text class="text" transform="translate(330 220)">💡解决方案</text>
-                    <text class="text" transform="translate(150 130)">"#,
-            user_info.user_summary.days_visited, // 访问天数
-            r#"</text>
-                    <text class="text" transform="translate(150 160)">"#,
-            duration_human_format(user_info.user_summary.time_read), // 阅读时间
-            r#"</text>
-                    <text class="text" transform="translate(150 190)">"#,
-            user_info.user_summary.topics_entered, // 浏览话题
-            r#"</text>
-                    <text class="text" transform="translate(150 220)">"#,
-            user_info.user_summary.posts_read_count, // 已读帖子
-            r#"</text>
-                    <text class="text" transform="translate(450 130)">"#,
-            user_info.user_summary.likes_given, // 已送出赞
-            r#"</text>
-                    <text class="text" transform="translate(450 160)">"#,
-            user_info.user_summary.likes_received, // 已收到赞
+                <line class="line" x1="30" y1="100" x2="570" y2="100"/>"#,
+            summary(&user_info.user_summary, messages),
+            r#"
+                <line class="line" x1="30" y1="235" x2="570" y2="235"/>"#,
+            self.show_badges.then(|| badges(user_info, messages)),
+            self.footer(user_info, failure, footer_y),
+            r#"
+            </svg>
+            "#
+        )
+    }
+
+    fn create_compact(
+        &self,
+        user_info: &model::UserInfo,
+        failure: Option<FailureKind>,
+        views: Option<u64>,
+    ) -> String {
+        let messages = self.locale.messages();
+
+        str_concat!(
+            self.header(600, 72),
+            avatar(user_info, 36, 36, 24),
+            views.map(|views| views_corner(views, messages)),
+            r#"
+                <text class="text" transform="translate(72 30)">"#,
+            name_line(user_info, &self.forum),
             r#"</text>
-                    <text class="text" transform="translate(450 190)">"#,
-            user_info.user_summary.post_count, // 创建帖子
+                <text class="text medium" transform="translate(72 54)">"#,
+            match (user_info.created, failure) {
+                (Some(_), _) => (
+                    Some((
+                        (
+                            "🛎️",
+                            user_info.user_summary.days_visited,
+                            " ",
+                            messages.day.of(user_info.user_summary.days_visited),
+                            " · ⌛",
+                            duration_human_format(user_info.user_summary.time_read, messages),
+                        ),
+                        " · 👍",
+                        user_info.user_summary.likes_received,
+                        " · 📖",
+                        user_info.user_summary.post_count,
+                        " · 💡",
+                        user_info.user_summary.solved_count,
+                    )),
+                    None,
+                ),
+                (None, Some(failure)) => (None, Some(status(failure, self.locale.messages()))),
+                (None, None) => (None, Some(self.locale.messages().fetching)),
+            },
             r#"</text>
-                    <text class="text" transform="translate(450 220)">"#,
-            user_info.user_summary.solved_count, // 解决方案
+            </svg>
+            "#
+        )
+    }
+
+    fn create_minimal(
+        &self,
+        user_info: &model::UserInfo,
+        failure: Option<FailureKind>,
+        views: Option<u64>,
+    ) -> String {
+        str_concat!(
+            self.header(360, 48),
+            avatar(user_info, 24, 24, 16),
+            r#"
+                <text class="text" transform="translate(48 30)">"#,
+            match (user_info.created, failure) {
+                (Some(_), _) => (
+                    Some((
+                        &user_info.user.username,
+                        " (",
+                        self.forum.branding.trust_level(user_info.user.trust_level),
+                        ")",
+                    )),
+                    None,
+                ),
+                (None, Some(failure)) => (None, Some(status(failure, self.locale.messages()))),
+                (None, None) => (None, Some(self.locale.messages().fetching)),
+            },
+            views.map(|views| (" · 👀 ", views)),
             r#"</text>
-                </g>
-                <line x1="30" y1="235" x2="570" y2="235" stroke="rgba(211, 211, 211, 1)" stroke-width="1"/>
+            </svg>
+            "#
+        )
+    }
+
+    #[inline]
+    /// `<svg>`, with title, style and background.
+    fn header(&self, width: u16, height: u16) -> impl StringExtT + '_ {
+        header(&self.forum, &self.style, "Card", width, height)
+    }
+
+    /// The footer: branding and when the data is fetched.
+    fn footer<'a>(
+        &'a self,
+        user_info: &'a model::UserInfo,
+        failure: Option<FailureKind>,
+        y: u16,
+    ) -> impl StringExtT + 'a {
+        let messages = self.locale.messages();
+
+        (
+            r#"
                 <g id="edit">
-                <text class="text" transform="translate(30 250)">Greeting SVG (originated from `linuxdo-card` by zjkal)</text>
-                <text class="text" transform="translate(330 250)">Updated: "#,
-            user_info
-                .created
-                .map(|instant| {
-                    (
-                        Some(
-                            (chrono::Local::now() - instant.elapsed())
+                <text class="text small" transform="translate(30 "#,
+            y,
+            r#")">"#,
+            &self.forum.branding.footer,
+            r#"</text>
+                <text class="text small" transform="translate(330 "#,
+            y,
+            r#")">"#,
+            fill(
+                messages.updated,
+                &[&user_info.created.map_or_else(
+                    || {
+                        Cow::Borrowed(match failure {
+                            Some(FailureKind::NotFound) => messages.footer_status[0],
+                            Some(FailureKind::Unavailable) => messages.footer_status[1],
+                            Some(FailureKind::Rejected(_)) => messages.footer_status[2],
+                            None => messages.fetching,
+                        })
+                    },
+                    |_| {
+                        Cow::Owned(
+                            user_info
+                                .fetched_at
                                 .with_timezone(&self.tz)
                                 .to_rfc3339_opts(chrono::SecondsFormat::Secs, false),
-                        ),
-                        None,
-                    )
-                })
-                .unwrap_or_else(|| (None, Some("... [FETCHING UPSTREAM]"))),
+                        )
+                    },
+                )],
+            ),
             r#"</text>
-                </g>
-            </svg>
-            "#
+                </g>"#,
         )
     }
 }
 
-#[tracing::instrument(level = "debug")]
-async fn get_or_fetch(user: &str, authorized: bool) -> Option<Arc<model::UserInfo>> {
-    let (cached, need_fetch) = cache::get_cache_or_fetch(user, authorized);
+/// `<svg>`, with title, style and background.
+fn header<'a>(
+    forum: &'a Forum,
+    style: &'a Style,
+    kind: &'static str,
+    width: u16,
+    height: u16,
+) -> impl StringExtT + 'a {
+    (
+        (
+            r#"
+            <svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" viewBox="0 0 "#,
+            width,
+            " ",
+            height,
+            r#"" fr-init-rc="true">
+                <title>"#,
+            &forum.branding.display_name,
+        ),
+        " ",
+        kind,
+        r#"</title>
+                <defs>
+                    <style>"#,
+        style.css(),
+        r#"
+                    </style>
+                    <clipPath id="avatar-clip" clipPathUnits="objectBoundingBox"><circle cx="0.5" cy="0.5" r="0.5"/></clipPath>
+                </defs>"#,
+        style.background(),
+    )
+}
+
+/// The visit count, at the top right corner.
+fn views_corner(views: u64, messages: &Messages) -> impl StringExtT {
+    (
+        r#"
+                <text class="text medium" x="570" y="30" text-anchor="end">👀 "#,
+        fill(messages.views.of(views), &[&views]),
+        "</text>",
+    )
+}
+
+/// Why there's no data.
+const fn status(failure: FailureKind, messages: &Messages) -> &'static str {
+    messages.status[match failure {
+        FailureKind::NotFound => 0,
+        FailureKind::Unavailable => 1,
+        FailureKind::Rejected(Rejected::NotAllowed) => 2,
+        FailureKind::Rejected(Rejected::QuotaExhausted) => 3,
+        FailureKind::Rejected(Rejected::Unclaimed) => 4,
+    }]
+}
+
+/// Username, name, trust level and title.
+fn name_line<'a>(user_info: &'a model::UserInfo, forum: &'a Forum) -> impl StringExtT + 'a {
+    (
+        &user_info.user.username,
+        user_info
+            .user
+            .name
+            .as_ref()
+            .map(|name| (" · ", name.as_ref())),
+        " (",
+        forum.branding.trust_level(user_info.user.trust_level),
+        ")",
+        user_info
+            .user
+            .title
+            .as_ref()
+            .map(|title| (" 「", title.as_ref(), "」")),
+    )
+}
+
+/// The summary rows, two columns of [`Metric::ALL`].
+fn summary<'a>(
+    user_summary: &'a model::UserSummary,
+    messages: &'a Messages,
+) -> impl StringExtT + 'a {
+    (
+        r#"
+                <g id="summary">"#,
+        Metric::ALL
+            .into_iter()
+            .enumerate()
+            .map(|(idx, metric)| {
+                let (x, y) = (30 + 300 * (idx as u16 / 4), 130 + 30 * (idx as u16 % 4));
+
+                (
+                    (
+                        r#"
+                    <text class="text" transform="translate("#,
+                        x,
+                        " ",
+                        y,
+                        r#")">"#,
+                        metric.label(messages),
+                        "</text>",
+                    ),
+                    (
+                        r#"
+                    <text class="text" transform="translate("#,
+                        x + 120,
+                        " ",
+                        y,
+                        r#")">"#,
+                        metric.format(user_summary, messages),
+                        "</text>",
+                    ),
+                )
+            })
+            .collect::<Vec<_>>(),
+        r#"
+                </g>"#,
+    )
+}
+
+/// The badges row: group flair and top badges.
+fn badges<'a>(user_info: &'a model::UserInfo, messages: &'a Messages) -> impl StringExtT + 'a {
+    (
+        r#"
+                <g id="badges">
+                    <text class="text medium" transform="translate(30 260)">"#,
+        messages.badges,
+        user_info
+            .user
+            .flair_name
+            .as_ref()
+            .map(|flair_name| ("  🚩", flair_name.as_ref())),
+        user_info
+            .badges
+            .iter()
+            .map(|badge| {
+                (
+                    match badge.badge_type_id {
+                        1 => "  🥇",
+                        2 => "  🥈",
+                        3 => "  🥉",
+                        _ => "  🎖️",
+                    },
+                    badge.name.as_ref(),
+                )
+            })
+            .collect::<Vec<_>>(),
+        (user_info.user.flair_name.is_none() && user_info.badges.is_empty())
+            .then_some(("  ", messages.no_badges)),
+        r#"</text>
+                </g>
+                <line class="line" x1="30" y1="270" x2="570" y2="270"/>"#,
+    )
+}
+
+/// The avatar in a circular clip (`avatar-clip` of [`header`]), or a
+/// placeholder with the initial of the username.
+fn avatar(user_info: &model::UserInfo, cx: u16, cy: u16, r: u16) -> impl StringExtT + '_ {
+    (
+        r#"
+                <g class="avatar">"#,
+        match &user_info.avatar {
+            Some(avatar) => (
+                Some((
+                    (r#"<image x=""#, cx - r, r#"" y=""#, cy - r),
+                    (r#"" width=""#, r * 2, r#"" height=""#, r * 2),
+                    r#"" clip-path="url(#avatar-clip)" href=""#,
+                    avatar.as_ref(),
+                    r#""/>"#,
+                )),
+                None,
+            ),
+            None => (
+                None,
+                Some((
+                    (r#"<circle cx=""#, cx, r#"" cy=""#, cy, r#"" r=""#, r),
+                    r#"" fill="rgba(211, 211, 211, 1)"/><text class="text" x=""#,
+                    cx,
+                    r#"" y=""#,
+                    cy + r / 3,
+                    r#"" style="font-size: "#,
+                    r,
+                    r#"px">"#,
+                    user_info
+                        .user
+                        .username
+                        .chars()
+                        .next()
+                        .filter(char::is_ascii_alphanumeric)
+                        .map_or_else(|| "?".to_string(), |c| c.to_ascii_uppercase().to_string()),
+                    "</text>",
+                )),
+            ),
+        },
+        r#"</g>"#,
+    )
+}
+
+/// Initialize the card cache, warming it up from persistent storage.
+///
+/// Should be called after the database is initialized.
+pub(crate) async fn init() {
+    profile::load().await;
+    cache::warm_up().await;
+    cache::try_init_cache_update_queue().await;
+}
+
+#[tracing::instrument(level = "debug", skip(forum), fields(forum = forum.name.as_ref()))]
+async fn get_or_fetch(forum: &Arc<Forum>, user: &str, requester: Requester) -> cache::Cached {
+    let (cached, need_fetch) = cache::get_cache_or_fetch(forum, user, requester);
 
     if let Some(need_fetch) = need_fetch {
         need_fetch.await;
@@ -203,20 +571,25 @@ const SPEC_WEEK_SECS: u64 = SPEC_DAY_SECS * 7;
 const SPEC_MONTH_SECS: u64 = SPEC_DAY_SECS * 30;
 const SPEC_YEAR_SECS: u64 = SPEC_DAY_SECS * 365;
 
-fn duration_human_format(duration: u64) -> impl StringExtT {
-    match duration {
+fn duration_human_format(duration: u64, messages: &Messages) -> String {
+    let (value, template) = match duration {
         0..SPEC_HOUR_SECS => (
             NumStr::new_default(duration as f64 / SPEC_MINUTE_SECS as f64).set_resize_len::<2>(),
-            " 分钟",
+            messages.duration[0],
         ),
         SPEC_HOUR_SECS.. => (
             NumStr::new_default(duration as f64 / SPEC_HOUR_SECS as f64).set_resize_len::<2>(),
-            " 小时",
+            messages.duration[1],
         ),
-    }
+    };
+
+    fill(template, &[&value.to_string_ext()])
 }
 
-fn cal_time_delta<Tz: chrono::TimeZone>(time: chrono::DateTime<Tz>) -> Option<impl StringExtT> {
+fn cal_time_delta<Tz: chrono::TimeZone>(
+    time: chrono::DateTime<Tz>,
+    messages: &Messages,
+) -> Option<String> {
     let created_delta = chrono::Local::now().signed_duration_since(time);
     let created_delta_sec = created_delta.num_seconds();
 
@@ -224,38 +597,18 @@ fn cal_time_delta<Tz: chrono::TimeZone>(time: chrono::DateTime<Tz>) -> Option<im
         tracing::error!("Invalid time setting, check the local clock!");
         None
     } else {
-        Some(match created_delta_sec as u64 {
-            0..SPEC_HOUR_SECS => (
-                NumStr::new_default(created_delta_sec as f64 / SPEC_MINUTE_SECS as f64)
-                    .set_integer_only::<true>(),
-                " 分钟前",
-            ),
-            SPEC_HOUR_SECS..SPEC_DAY_SECS => (
-                NumStr::new_default(created_delta_sec as f64 / SPEC_HOUR_SECS as f64)
-                    .set_integer_only::<true>(),
-                " 个小时前",
-            ),
-            SPEC_DAY_SECS..SPEC_WEEK_SECS => (
-                NumStr::new_default(created_delta_sec as f64 / SPEC_DAY_SECS as f64)
-                    .set_integer_only::<true>(),
-                " 天前",
-            ),
-            SPEC_WEEK_SECS..SPEC_MONTH_SECS => (
-                NumStr::new_default(created_delta_sec as f64 / SPEC_WEEK_SECS as f64)
-                    .set_integer_only::<true>(),
-                " 周前",
-            ),
-            SPEC_MONTH_SECS..SPEC_YEAR_SECS => (
-                NumStr::new_default(created_delta_sec as f64 / SPEC_MONTH_SECS as f64)
-                    .set_integer_only::<true>(),
-                " 个月前",
-            ),
-            SPEC_YEAR_SECS.. => (
-                NumStr::new_default(created_delta_sec as f64 / SPEC_YEAR_SECS as f64)
-                    .set_integer_only::<true>(),
-                " 年前",
-            ),
-        })
+        let created_delta_sec = created_delta_sec as u64;
+
+        let (value, idx) = match created_delta_sec {
+            0..SPEC_HOUR_SECS => (created_delta_sec / SPEC_MINUTE_SECS, 0),
+            SPEC_HOUR_SECS..SPEC_DAY_SECS => (created_delta_sec / SPEC_HOUR_SECS, 1),
+            SPEC_DAY_SECS..SPEC_WEEK_SECS => (created_delta_sec / SPEC_DAY_SECS, 2),
+            SPEC_WEEK_SECS..SPEC_MONTH_SECS => (created_delta_sec / SPEC_WEEK_SECS, 3),
+            SPEC_MONTH_SECS..SPEC_YEAR_SECS => (created_delta_sec / SPEC_MONTH_SECS, 4),
+            SPEC_YEAR_SECS.. => (created_delta_sec / SPEC_YEAR_SECS, 5),
+        };
+
+        Some(fill(messages.ago[idx].of(value), &[&value]))
     }
 }
 
@@ -270,16 +623,38 @@ fn cal_time_delta<Tz: chrono::TimeZone>(time: chrono::DateTime<Tz>) -> Option<im
 //     duration_human_format(376973).to_string_ext();
 // }
 
-// #[tokio::test]
-// async fn test() {
-//     macro_toolset::init_tracing_simple!();
+#[tokio::test]
+async fn test_fixtures() {
+    let forum = Arc::new(upstream::fixture_forum("test-card-fixtures"));
 
-//     let data = &*get_or_fetch("hantong").await.unwrap();
-//     data;
-//     let data = &*get_or_fetch("hantong").await.unwrap();
-//     data;
+    let card = |user| {
+        LinuxDoCardImpl::new(
+            forum.clone(),
+            user,
+            chrono_tz::Asia::Shanghai,
+            Requester::default(),
+        )
+        .set_show_badges(true)
+        .generate(None)
+    };
 
-//     tokio::time::sleep(std::time::Duration::from_secs(6)).await;
-//     let data = &*get_or_fetch("hantong").await.unwrap();
-//     data;
-// }
+    // Cached ones are served without asking the fetch policy.
+    let cached = cache::refresh(&forum, "Hantong").await.unwrap().unwrap();
+    assert!(cached.contains("\"likes_received\": 2345"));
+
+    let svg = card("hantong").await;
+    assert!(svg.contains("hantong · Hantong Chen (🚅三级大佬)"));
+    assert!(svg.contains("data:image/png;base64,UE5H"));
+    assert!(svg.contains("Rust Evangelist"));
+
+    // Negative cached
+    assert_eq!(
+        cache::refresh(&forum, "gone").await,
+        Err(FailureKind::NotFound)
+    );
+    assert!(
+        card("gone")
+            .await
+            .contains(status(FailureKind::NotFound, Locale::ZhCn.messages()))
+    );
+}
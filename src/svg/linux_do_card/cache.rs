@@ -1,20 +1,37 @@
 //! Linux.do cards, 300s cache
+//!
+//! Refreshing is demand driven: only users requested within
+//! [`ACTIVE_WINDOW`] are refreshed when expired, idle ones are evicted.
 
 use std::{
-    collections::VecDeque,
+    cmp::Reverse,
+    collections::BinaryHeap,
+    fmt,
     hash::Hash,
-    sync::{Arc, LazyLock, OnceLock},
+    sync::{
+        Arc, LazyLock, OnceLock,
+        atomic::{AtomicU64, Ordering},
+    },
     time::{Duration, Instant},
 };
 
+use chrono::Utc;
 use dashmap::DashMap;
 use macro_toolset::wrapper;
 use parking_lot::Mutex;
-use tokio::task::JoinHandle;
+use serde::Serialize;
+use tokio::{sync::Notify, task::JoinHandle};
 
-use super::{model::UserInfo, upstream};
+use super::{
+    limiter::{self, Target},
+    model::{Activity, UserInfo},
+    policy::{self, Rejected, Requester},
+    upstream::{self, FailureKind},
+};
+use crate::{config::Forum, db::Persistent};
 
-type CacheUpdateQueue = Mutex<VecDeque<(Arc<str>, Instant)>>;
+/// Scheduled refresh: (due, key, `created` of the entry when scheduled)
+type Scheduled = Reverse<(Instant, CacheKey, Instant)>;
 
 #[cfg(not(debug_assertions))]
 /// Default cache TTL, 300s
@@ -24,153 +41,706 @@ const CACHE_TTL: u64 = 300;
 /// Default cache TTL, 300s
 const CACHE_TTL: u64 = 15;
 
+#[cfg(not(debug_assertions))]
+/// Activity cache TTL, 3600s
+const ACTIVITY_CACHE_TTL: u64 = 3600;
+
+#[cfg(debug_assertions)]
+/// Activity cache TTL, 3600s
+const ACTIVITY_CACHE_TTL: u64 = 60;
+
+#[cfg(not(debug_assertions))]
+/// Users requested within this window are refreshed when expired, 1800s
+const ACTIVE_WINDOW: u64 = 1800;
+
+#[cfg(debug_assertions)]
+/// Users requested within this window are refreshed when expired, 1800s
+const ACTIVE_WINDOW: u64 = 60;
+
 /// Desired max key count, 5120
 const DESIRED_MAX_KEY_COUNT: usize = 5120;
 
-static FETCH_PROCESSING: LazyLock<DashMap<Arc<str>, (), foldhash::fast::RandomState>> =
+static FETCH_PROCESSING: LazyLock<DashMap<CacheKey, (), foldhash::fast::RandomState>> =
     LazyLock::new(|| {
         DashMap::with_capacity_and_hasher(128, foldhash::fast::RandomState::default())
     });
 
+/// Negative cache: failure kind and when
+static NEGATIVE_CACHE: LazyLock<
+    DashMap<CacheKey, (FailureKind, Instant), foldhash::fast::RandomState>,
+> = LazyLock::new(|| {
+    DashMap::with_capacity_and_hasher(128, foldhash::fast::RandomState::default())
+});
+
 /// static CACHE map
-static CACHE: LazyLock<Cache<Arc<str>, Arc<UserInfo>>> =
+static CACHE: LazyLock<Cache<CacheKey, Arc<UserInfo>>> =
     LazyLock::new(|| Cache::with_capacity(DESIRED_MAX_KEY_COUNT));
 
-static CACHE_UPDATE_QUEUE: LazyLock<CacheUpdateQueue> =
-    LazyLock::new(|| Mutex::new(VecDeque::with_capacity(DESIRED_MAX_KEY_COUNT)));
+/// Activity cache, see [`get_activity_or_fetch`]
+static ACTIVITY_CACHE: LazyLock<Cache<CacheKey, Arc<Activity>>> =
+    LazyLock::new(|| Cache::with_capacity(128));
+
+static ACTIVITY_FETCH_PROCESSING: LazyLock<DashMap<CacheKey, (), foldhash::fast::RandomState>> =
+    LazyLock::new(|| {
+        DashMap::with_capacity_and_hasher(128, foldhash::fast::RandomState::default())
+    });
+
+/// Negative cache of activity, apart from the card one
+static ACTIVITY_NEGATIVE_CACHE: LazyLock<
+    DashMap<CacheKey, (FailureKind, Instant), foldhash::fast::RandomState>,
+> = LazyLock::new(|| {
+    DashMap::with_capacity_and_hasher(128, foldhash::fast::RandomState::default())
+});
+
+/// When each user was last requested
+static LAST_REQUESTED: LazyLock<DashMap<CacheKey, Instant, foldhash::fast::RandomState>> =
+    LazyLock::new(|| {
+        DashMap::with_capacity_and_hasher(
+            DESIRED_MAX_KEY_COUNT,
+            foldhash::fast::RandomState::default(),
+        )
+    });
+
+/// Refresh queue, the earliest due first
+static REFRESH_QUEUE: LazyLock<Mutex<BinaryHeap<Scheduled>>> =
+    LazyLock::new(|| Mutex::new(BinaryHeap::with_capacity(DESIRED_MAX_KEY_COUNT)));
+
+/// Wake up the refresh queue task when something is scheduled
+static REFRESH_QUEUE_NOTIFY: Notify = Notify::const_new();
 
 static CACHE_QUEUE_TASK_HANDLE: OnceLock<JoinHandle<()>> = OnceLock::new();
 
+static METRICS_REFRESHED: AtomicU64 = AtomicU64::new(0);
+static METRICS_EVICTED: AtomicU64 = AtomicU64::new(0);
+static METRICS_REFRESH_LAG_LAST: AtomicU64 = AtomicU64::new(0);
+static METRICS_REFRESH_LAG_MAX: AtomicU64 = AtomicU64::new(0);
+
 pub(super) async fn try_init_cache_update_queue() {
     if CACHE_QUEUE_TASK_HANDLE.get().is_none() {
         let _ = CACHE_QUEUE_TASK_HANDLE.set(tokio::spawn(async {
-            const CACHE_TTL_DUR: Duration = Duration::from_secs(CACHE_TTL);
-
-            let mut to_update: Option<Arc<str>> = None;
-            let mut to_sleep: Option<Duration> = None;
             loop {
-                if let Some(to_sleep) = to_sleep.take() {
-                    tracing::trace!(to_sleep = ?to_sleep, "cache_update_queue: to sleep!");
-                    tokio::time::sleep(to_sleep).await;
-                }
-
-                if let Some(to_update) = to_update.take() {
-                    tracing::trace!(to_update = ?to_update, "cache_update_queue: to update!");
-                    tokio::spawn(async move {
-                        match upstream::fetch(&to_update).await {
-                            Ok(value) => write_cache(value).await,
-                            Err(e) => {
-                                tracing::error!(
-                                    to_update = to_update.as_ref(),
-                                    "background update error: {e:#?}"
-                                );
-                            },
-                        };
-                    });
-                }
+                let next_due = REFRESH_QUEUE.lock().peek().map(|item| item.0.0);
 
-                {
-                    let value = CACHE_UPDATE_QUEUE.lock().pop_back();
-                    if let Some((key, instant)) = value {
-                        tracing::trace!(key = ?key, "cache_update_queue: handling!");
-
-                        if let Some(to_sleep_dur) = CACHE_TTL_DUR.checked_sub(instant.elapsed()) {
-                            to_sleep.replace(to_sleep_dur);
-                        } else {
-                            tracing::debug!(key = ?key, "cache_update_queue: cache expired, refreshing")
+                match next_due {
+                    Some(due) if due <= Instant::now() => {}
+                    Some(due) => {
+                        tracing::trace!(due = ?due, "cache_update_queue: to sleep!");
+                        tokio::select! {
+                            () = tokio::time::sleep_until(due.into()) => {}
+                            () = REFRESH_QUEUE_NOTIFY.notified() => {}
                         }
-
-                        to_update.replace(key);
-                    } else {
+                        continue;
+                    }
+                    None => {
                         tracing::trace!("No cache queue tasks...");
-                        to_sleep.replace(CACHE_TTL_DUR);
+                        REFRESH_QUEUE_NOTIFY.notified().await;
+                        continue;
                     }
                 }
+
+                let item = REFRESH_QUEUE.lock().pop();
+                if let Some(Reverse((due, key, created))) = item {
+                    handle_scheduled(key, due, created);
+                }
             }
         }));
     }
 }
 
+/// Schedule a refresh (or eviction) check of the given entry.
+fn schedule(key: CacheKey, due: Instant, created: Instant) {
+    REFRESH_QUEUE.lock().push(Reverse((due, key, created)));
+    REFRESH_QUEUE_NOTIFY.notify_one();
+}
+
+/// Refresh the entry if the user is still active, or evict it.
+fn handle_scheduled(key: CacheKey, due: Instant, created: Instant) {
+    if CACHE.get(&key).is_none_or(|v| v.1 != created) {
+        tracing::trace!(key = %key, "cache_update_queue: superseded or removed, skip");
+        return;
+    }
+
+    if !is_active(&key) {
+        tracing::debug!(key = %key, "cache_update_queue: idle, evicted");
+        CACHE.remove(&key);
+        LAST_REQUESTED.remove(&key);
+        METRICS_EVICTED.fetch_add(1, Ordering::Relaxed);
+        return;
+    }
+
+    if FETCH_PROCESSING.insert(key.clone(), ()).is_some() {
+        tracing::debug!(key = %key, "cache_update_queue: processing, skip");
+        return;
+    }
+
+    let lag = due.elapsed().as_millis() as u64;
+    METRICS_REFRESH_LAG_LAST.store(lag, Ordering::Relaxed);
+    METRICS_REFRESH_LAG_MAX.fetch_max(lag, Ordering::Relaxed);
+    METRICS_REFRESHED.fetch_add(1, Ordering::Relaxed);
+
+    tracing::debug!(key = %key, lag, "cache_update_queue: cache expired, refreshing");
+
+    tokio::spawn(async move {
+        let Some(forum) = Forum::find(Some(&key.forum)) else {
+            tracing::debug!(key = %key, "forum removed, skip");
+            FETCH_PROCESSING.remove(&key);
+            CACHE.remove(&key);
+            return;
+        };
+
+        let _ = fetch_and_write(&forum, key).await;
+    });
+}
+
+#[inline]
+/// Whether the user was requested within [`ACTIVE_WINDOW`].
+fn is_active(key: &CacheKey) -> bool {
+    LAST_REQUESTED
+        .get(key)
+        .is_some_and(|v| v.elapsed().as_secs() <= ACTIVE_WINDOW)
+}
+
+/// Record a request of the given user, returns if it's the first one within
+/// [`ACTIVE_WINDOW`].
+fn touch(key: &CacheKey) -> bool {
+    let first = !is_active(key);
+
+    LAST_REQUESTED.insert(key.clone(), Instant::now());
+
+    if LAST_REQUESTED.len() > DESIRED_MAX_KEY_COUNT * 2 {
+        tokio::spawn(async {
+            LAST_REQUESTED.retain(|_, v| v.elapsed().as_secs() <= ACTIVE_WINDOW);
+        });
+    }
+
+    first
+}
+
+/// Warm up the cache from persistent storage, respecting [`CACHE_TTL`] and
+/// [`DESIRED_MAX_KEY_COUNT`].
+///
+/// Expired entries are loaded too (better than nothing). Loaded users will be
+/// refreshed once requested, or evicted if not requested within
+/// [`ACTIVE_WINDOW`].
+pub(super) async fn warm_up() {
+    let entries = match Persistent::user_cache_load(DESIRED_MAX_KEY_COUNT).await {
+        Ok(entries) => entries,
+        Err(e) => {
+            tracing::error!("Load user cache from database error: {e:#?}");
+            return;
+        }
+    };
+
+    let now = Utc::now().timestamp();
+    let evict_at = Instant::now() + Duration::from_secs(ACTIVE_WINDOW);
+    let mut loaded = 0;
+
+    for (forum, user, fetched_at, data) in entries {
+        let mut value: UserInfo = match serde_json::from_str(&data) {
+            Ok(value) => value,
+            Err(e) => {
+                tracing::warn!("Invalid cached data of {forum}/{user}: {e}");
+                continue;
+            }
+        };
+
+        let age = Duration::from_secs(now.saturating_sub(fetched_at).max(0) as u64);
+
+        // `Instant` may not be able to go back that far (e.g. just booted), then
+        // just make it expired.
+        let created = Instant::now()
+            .checked_sub(age)
+            .or_else(|| Instant::now().checked_sub(Duration::from_secs(CACHE_TTL + 1)))
+            .unwrap_or_else(Instant::now);
+        value.created = Some(created);
+
+        let key = CacheKey { forum, user };
+        CACHE.insert(key.clone(), (Arc::new(value), created));
+        schedule(key, evict_at, created);
+        loaded += 1;
+    }
+
+    tracing::info!("Loaded {loaded} cached users from database");
+}
+
+#[derive(Debug, Clone)]
+/// Cached state of a user
+pub(super) enum Cached<T = Arc<UserInfo>> {
+    /// Cached data, maybe stale
+    Hit(T),
+
+    /// Fetch failed recently (negative cache)
+    Failed(FailureKind),
+
+    /// Nothing cached, and fetching is rejected by the fetch policy
+    Rejected(Rejected),
+
+    /// Nothing cached
+    Miss,
+}
+
 #[must_use = "must handle if need fetch!!!"]
 /// Get cache
 ///
-/// Returns: data, update async task (you may await it or just throw it away)
+/// Returns: cached state, update async task (you may await it or just throw it
+/// away)
 pub(super) fn get_cache_or_fetch(
-    key: &str,
-    authorized: bool,
+    forum: &Arc<Forum>,
+    user: &str,
+    requester: Requester,
+) -> (Cached, Option<impl Future + Send + Sync + 'static>) {
+    let key = CacheKey::new(forum, user);
+
+    let (mut cached, need_fetch) = match CACHE.get(&key).map(|v| (v.0.clone(), v.1)) {
+        Some((value, created)) => {
+            let expired = created.elapsed().as_secs() > CACHE_TTL;
+
+            // Back from idle, keep it refreshed from now on.
+            if touch(&key) && !expired {
+                schedule(
+                    key.clone(),
+                    created + Duration::from_secs(CACHE_TTL),
+                    created,
+                );
+            }
+
+            (Cached::Hit(value), expired)
+        }
+        None => match NEGATIVE_CACHE.get(&key) {
+            Some(v) if v.1.elapsed().as_secs() <= v.0.ttl() => (Cached::Failed(v.0), false),
+            _ => (Cached::Miss, true),
+        },
+    };
+
+    let key = if need_fetch {
+        match FETCH_PROCESSING.entry(key.clone()) {
+            dashmap::Entry::Vacant(v) => match policy::check(forum, user, requester) {
+                Ok(()) => {
+                    v.insert(());
+                    touch(&key);
+                    Some(key)
+                }
+                Err(rejected) => {
+                    // Stale data is still better.
+                    if matches!(cached, Cached::Miss) {
+                        cached = Cached::Rejected(rejected);
+                    }
+                    None
+                }
+            },
+            dashmap::Entry::Occupied(_) => {
+                tracing::debug!(key = %key, "Processing, just wait...");
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    let forum = forum.clone();
+    let async_task = key.map(|key| async move {
+        tracing::debug!("Cache missed or expired, try fetch in background");
+
+        tokio::spawn(async move {
+            let _ = fetch_and_write(&forum, key).await;
+        });
+    });
+
+    (cached, async_task)
+}
+
+#[must_use = "must handle if need fetch!!!"]
+/// Get cached activity of the user, like [`get_cache_or_fetch`].
+///
+/// Activity is fetched on demand only, and not persisted.
+pub(super) fn get_activity_or_fetch(
+    forum: &Arc<Forum>,
+    user: &str,
+    requester: Requester,
 ) -> (
-    Option<Arc<UserInfo>>,
+    Cached<Arc<Activity>>,
     Option<impl Future + Send + Sync + 'static>,
 ) {
-    let (data, key) = match CACHE.get(key) {
-        Some(v) => (
-            Some(v.0.clone()),
-            if v.1.elapsed().as_secs() > CACHE_TTL {
-                Some(key.into())
-            } else {
-                None
-            },
+    let key = CacheKey::new(forum, user);
+
+    let (mut cached, need_fetch) = match ACTIVITY_CACHE.get(&key).map(|v| (v.0.clone(), v.1)) {
+        Some((value, created)) => (
+            Cached::Hit(value),
+            created.elapsed().as_secs() > ACTIVITY_CACHE_TTL,
         ),
-        None => (None, {
-            let key: Arc<str> = key.into();
+        None => match ACTIVITY_NEGATIVE_CACHE.get(&key) {
+            Some(v) if v.1.elapsed().as_secs() <= v.0.ttl() => (Cached::Failed(v.0), false),
+            _ => (Cached::Miss, true),
+        },
+    };
 
-            match FETCH_PROCESSING.entry(key.clone()) {
-                dashmap::Entry::Vacant(v) => {
+    let key = if need_fetch {
+        match ACTIVITY_FETCH_PROCESSING.entry(key.clone()) {
+            dashmap::Entry::Vacant(v) => match policy::check(forum, user, requester) {
+                Ok(()) => {
                     v.insert(());
                     Some(key)
                 }
-                dashmap::Entry::Occupied(_) => {
-                    tracing::debug!(key = ?key, "Processing, just wait...");
+                Err(rejected) => {
+                    if matches!(cached, Cached::Miss) {
+                        cached = Cached::Rejected(rejected);
+                    }
                     None
                 }
+            },
+            dashmap::Entry::Occupied(_) => {
+                tracing::debug!(key = %key, "Processing, just wait...");
+                None
             }
-        }),
+        }
+    } else {
+        None
     };
 
+    let forum = forum.clone();
     let async_task = key.map(|key| async move {
-        if authorized {
-            tracing::debug!("Cache missed, try fetch in background");
+        tokio::spawn(async move {
+            match upstream::fetch_activity(&forum, &key.user).await {
+                Ok(value) => {
+                    if let Some(created) = value.created {
+                        ACTIVITY_CACHE.insert(key.clone(), (Arc::new(value), created));
+                    }
 
-            tokio::spawn(async move {
-                match upstream::fetch(&key).await {
-                    Ok(value) => write_cache(value).await,
-                    Err(e) => {
-                        tracing::error!("Fetch upstream data error: {e:#?}");
+                    ACTIVITY_NEGATIVE_CACHE.remove(&key);
+
+                    if ACTIVITY_CACHE.len() > DESIRED_MAX_KEY_COUNT {
+                        ACTIVITY_CACHE.retain_ttl(ACTIVITY_CACHE_TTL);
                     }
                 }
-            });
-        } else {
-            tracing::debug!("Cache missed, but not authorized user!");
-        }
+                Err(e) => {
+                    tracing::error!(key = %key, "Fetch upstream activity error: {e:#?}");
+
+                    ACTIVITY_NEGATIVE_CACHE
+                        .insert(key.clone(), (FailureKind::of(&e), Instant::now()));
+
+                    if ACTIVITY_NEGATIVE_CACHE.len() > DESIRED_MAX_KEY_COUNT {
+                        ACTIVITY_NEGATIVE_CACHE.retain(|_, v| v.1.elapsed().as_secs() <= v.0.ttl());
+                    }
+                }
+            }
+
+            ACTIVITY_FETCH_PROCESSING.remove(&key);
+        });
     });
 
-    (data, async_task)
+    (cached, async_task)
+}
+
+/// Fetch from upstream (with avatar), then write cache or negative cache.
+async fn fetch_and_write(forum: &Forum, key: CacheKey) -> Result<(), FailureKind> {
+    match upstream::fetch(forum, &key.user).await {
+        Ok(mut value) => {
+            let previous = CACHE.get(&key).map(|v| v.0.clone());
+            upstream::attach_avatar(forum, &mut value, previous.as_deref()).await;
+
+            write_cache(key, value).await;
+
+            Ok(())
+        }
+        Err(e) => {
+            tracing::error!(key = %key, "Fetch upstream data error: {e:#?}");
+
+            let kind = FailureKind::of(&e);
+            write_negative_cache(key, kind);
+
+            Err(kind)
+        }
+    }
+}
+
+/// Write negative cache, clearing the in-flight marker.
+///
+/// For [`FailureKind::NotFound`], the stale data is removed too. Otherwise the
+/// stale data is kept, and retried after the negative cache expires.
+fn write_negative_cache(key: CacheKey, kind: FailureKind) {
+    FETCH_PROCESSING.remove(&key);
+
+    if kind == FailureKind::NotFound {
+        CACHE.remove(&key);
+        LAST_REQUESTED.remove(&key);
+    } else if let Some(created) = CACHE.get(&key).map(|v| v.1) {
+        schedule(
+            key.clone(),
+            Instant::now() + Duration::from_secs(kind.ttl()),
+            created,
+        );
+    } else {
+        // Nothing to retry
+    }
+
+    NEGATIVE_CACHE.insert(key, (kind, Instant::now()));
+
+    if NEGATIVE_CACHE.len() > DESIRED_MAX_KEY_COUNT {
+        tokio::spawn(async {
+            NEGATIVE_CACHE.retain(|_, v| v.1.elapsed().as_secs() <= v.0.ttl());
+        });
+    }
 }
 
 /// Write cache
-pub(super) async fn write_cache(value: impl Into<Arc<UserInfo>>) {
+pub(super) async fn write_cache(key: CacheKey, value: impl Into<Arc<UserInfo>>) {
     let value = value.into();
     if let Some(created) = value.created {
-        let key = value.user.username.clone();
         FETCH_PROCESSING.remove(&key);
-        CACHE.insert(key.clone(), (value, created));
+        NEGATIVE_CACHE.remove(&key);
+        CACHE.insert(key.clone(), (value.clone(), created));
 
-        if CACHE.len() > DESIRED_MAX_KEY_COUNT {
-            tokio::spawn(async {
-                CACHE.retain_ttl(CACHE_TTL);
+        {
+            let key = key.clone();
+            tokio::spawn(async move {
+                match serde_json::to_string(&*value) {
+                    Ok(data) => {
+                        Persistent::user_cache_write((
+                            key.forum,
+                            key.user,
+                            value.fetched_at.timestamp(),
+                            data,
+                        ))
+                        .await;
+                    }
+                    Err(e) => {
+                        tracing::error!(key = %key, "Serialize user cache error: {e:#?}");
+                    }
+                }
             });
         }
 
-        let mut cache_update_queue = CACHE_UPDATE_QUEUE.lock();
-        cache_update_queue.push_front((key, created));
-        if cache_update_queue.len() > DESIRED_MAX_KEY_COUNT {
-            drop(cache_update_queue);
+        if CACHE.len() > DESIRED_MAX_KEY_COUNT {
             tokio::spawn(async {
-                CACHE_UPDATE_QUEUE
-                    .lock()
-                    .truncate(DESIRED_MAX_KEY_COUNT / 2);
+                // Idle ones first, then the expired ones.
+                CACHE.retain(|key, _| is_active(key));
+
+                if CACHE.len() > DESIRED_MAX_KEY_COUNT {
+                    CACHE.retain_ttl(CACHE_TTL);
+                }
             });
         }
+
+        schedule(key, created + Duration::from_secs(CACHE_TTL), created);
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+/// Cache metrics
+pub(crate) struct Metrics {
+    /// Cached users
+    pub cached: usize,
+
+    /// Negative cached users
+    pub negative_cached: usize,
+
+    /// Users being fetched
+    pub in_flight: usize,
+
+    /// Users requested within [`ACTIVE_WINDOW`] (approximately)
+    pub active: usize,
+
+    /// Refresh queue depth
+    pub queue_depth: usize,
+
+    /// Background refreshes started
+    pub refreshed_total: u64,
+
+    /// Idle users evicted
+    pub evicted_total: u64,
+
+    /// Refresh lag of the last background refresh, in milliseconds
+    pub refresh_lag_last_ms: u64,
+
+    /// Max refresh lag, in milliseconds
+    pub refresh_lag_max_ms: u64,
+}
+
+/// Get cache metrics.
+pub(crate) fn metrics() -> Metrics {
+    Metrics {
+        cached: CACHE.len(),
+        negative_cached: NEGATIVE_CACHE.len(),
+        in_flight: FETCH_PROCESSING.len(),
+        active: LAST_REQUESTED.len(),
+        queue_depth: REFRESH_QUEUE.lock().len(),
+        refreshed_total: METRICS_REFRESHED.load(Ordering::Relaxed),
+        evicted_total: METRICS_EVICTED.load(Ordering::Relaxed),
+        refresh_lag_last_ms: METRICS_REFRESH_LAG_LAST.load(Ordering::Relaxed),
+        refresh_lag_max_ms: METRICS_REFRESH_LAG_MAX.load(Ordering::Relaxed),
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+/// Cached user, see [`list`]
+pub(crate) struct Entry {
+    /// Forum name
+    pub forum: Arc<str>,
+
+    /// Username, lowercased
+    pub user: Arc<str>,
+
+    /// Age of the cached data, in seconds
+    pub age_secs: u64,
+
+    /// Whether the cached data is expired
+    pub expired: bool,
+
+    /// Whether the user was requested within [`ACTIVE_WINDOW`]
+    pub active: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+/// Negative cached user, see [`list`]
+pub(crate) struct NegativeEntry {
+    /// Forum name
+    pub forum: Arc<str>,
+
+    /// Username, lowercased
+    pub user: Arc<str>,
+
+    /// Why the fetch failed
+    pub kind: FailureKind,
+
+    /// Age of the negative cache, in seconds
+    pub age_secs: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+/// Snapshot of the cache, see [`list`]
+pub(crate) struct Listing {
+    /// Cached users
+    pub users: Vec<Entry>,
+
+    /// Negative cached users
+    pub negative: Vec<NegativeEntry>,
+
+    /// Users being fetched, `{forum}/{user}`
+    pub in_flight: Vec<String>,
+}
+
+/// List cached users, negative cached users and in-flight fetches, sorted.
+pub(crate) fn list() -> Listing {
+    let mut users: Vec<_> = CACHE
+        .iter()
+        .map(|entry| {
+            let age_secs = entry.1.elapsed().as_secs();
+
+            Entry {
+                forum: entry.key().forum.clone(),
+                user: entry.key().user.clone(),
+                age_secs,
+                expired: age_secs > CACHE_TTL,
+                active: is_active(entry.key()),
+            }
+        })
+        .collect();
+    users.sort_unstable_by(|a, b| (&a.forum, &a.user).cmp(&(&b.forum, &b.user)));
+
+    let mut negative: Vec<_> = NEGATIVE_CACHE
+        .iter()
+        .map(|entry| NegativeEntry {
+            forum: entry.key().forum.clone(),
+            user: entry.key().user.clone(),
+            kind: entry.0,
+            age_secs: entry.1.elapsed().as_secs(),
+        })
+        .collect();
+    negative.sort_unstable_by(|a, b| (&a.forum, &a.user).cmp(&(&b.forum, &b.user)));
+
+    let mut in_flight: Vec<_> = FETCH_PROCESSING
+        .iter()
+        .chain(ACTIVITY_FETCH_PROCESSING.iter())
+        .map(|entry| entry.key().to_string())
+        .collect();
+    in_flight.sort_unstable();
+    in_flight.dedup();
+
+    Listing {
+        users,
+        negative,
+        in_flight,
+    }
+}
+
+/// Get the raw cached data of the given user, as JSON.
+pub(crate) fn show(forum: &Forum, user: &str) -> Option<String> {
+    let value = CACHE.get(&CacheKey::new(forum, user))?.0.clone();
+
+    serde_json::to_string_pretty(&*value)
+        .inspect_err(|e| tracing::error!("Serialize user cache error: {e:#?}"))
+        .ok()
+}
+
+/// Purge everything cached of the given user, including the persisted one.
+///
+/// Returns if the user was cached at all.
+pub(crate) async fn purge(forum: &Forum, user: &str) -> bool {
+    let key = CacheKey::new(forum, user);
+
+    let cached = CACHE.remove(&key).is_some();
+    let negative_cached = NEGATIVE_CACHE.remove(&key).is_some();
+    let activity_cached = ACTIVITY_CACHE.remove(&key).is_some();
+    let activity_negative_cached = ACTIVITY_NEGATIVE_CACHE.remove(&key).is_some();
+    LAST_REQUESTED.remove(&key);
+
+    Persistent::user_cache_delete(key.forum, key.user).await;
+
+    cached || negative_cached || activity_cached || activity_negative_cached
+}
+
+/// Refresh the given user from upstream now, regardless of the fetch policy,
+/// whether it's being fetched, and the backoff of the user (which is cleared).
+///
+/// The forum's rate limit and circuit breaker still apply.
+///
+/// Returns the refreshed data as JSON.
+pub(crate) async fn refresh(forum: &Forum, user: &str) -> Result<Option<String>, FailureKind> {
+    let key = CacheKey::new(forum, user);
+
+    limiter::on_user_success(forum, user, Target::Card);
+    FETCH_PROCESSING.insert(key.clone(), ());
+    fetch_and_write(forum, key).await?;
+
+    Ok(show(forum, user))
+}
+
+/// Clear the in-flight markers, in case some fetch got stuck.
+///
+/// Returns how many are cleared.
+pub(crate) fn clear_in_flight() -> usize {
+    let cleared = FETCH_PROCESSING.len() + ACTIVITY_FETCH_PROCESSING.len();
+
+    FETCH_PROCESSING.clear();
+    ACTIVITY_FETCH_PROCESSING.clear();
+
+    cleared
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// Cache key: forum name and (lowercased, since case insensitive) username
+pub(super) struct CacheKey {
+    /// Forum name
+    pub forum: Arc<str>,
+
+    /// Username, lowercased
+    pub user: Arc<str>,
+}
+
+impl CacheKey {
+    #[inline]
+    /// Create a new [`CacheKey`].
+    pub(super) fn new(forum: &Forum, user: &str) -> Self {
+        Self {
+            forum: forum.name.clone(),
+            user: Arc::from(user.to_lowercase()),
+        }
+    }
+}
+
+impl fmt::Display for CacheKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.forum, self.user)
     }
 }
 
@@ -195,3 +765,22 @@ impl<K: Hash + Eq + Clone, V> Cache<K, V> {
             .retain(|_, v| v.1.elapsed().as_secs() <= target_ttl);
     }
 }
+
+#[tokio::test]
+async fn test_idle_eviction() {
+    let key = CacheKey {
+        forum: Arc::from("test-eviction"),
+        user: Arc::from("idle"),
+    };
+    let created = Instant::now();
+    CACHE.insert(key.clone(), (Arc::new(UserInfo::default()), created));
+
+    // Superseded by a newer write, skip
+    handle_scheduled(key.clone(), created, created + Duration::from_millis(1));
+    assert!(CACHE.contains_key(&key));
+
+    // Never requested, evicted
+    handle_scheduled(key.clone(), created, created);
+    assert!(!CACHE.contains_key(&key));
+    assert!(metrics().evicted_total >= 1);
+}
@@ -0,0 +1,262 @@
+//! Linux.do comparison card, two users side by side
+
+use std::sync::Arc;
+
+use macro_toolset::{str_concat, string::StringExtT};
+
+use super::{
+    FailureKind, avatar, cache, get_or_fetch, header, layout::Style, leaderboard::Metric,
+    model::UserInfo, policy::Requester, status,
+};
+use crate::{
+    config::Forum,
+    svg::locale::{Locale, Messages},
+};
+
+/// Center of the columns of the two users
+const COLUMNS: [u16; 2] = [330, 480];
+
+/// Where the table ends
+const TABLE_END: u16 = 100 + Metric::ALL.len() as u16 * 30;
+
+#[derive(Debug, Clone)]
+pub(crate) struct LinuxDoCompareImpl<'i> {
+    forum: Arc<Forum>,
+    users: [(&'i str, Requester); 2],
+    style: Style,
+    locale: Locale,
+}
+
+impl<'i> LinuxDoCompareImpl<'i> {
+    /// Create a new [`LinuxDoCompareImpl`].
+    ///
+    /// `users`: the two users and who is requesting each of them, see
+    /// [`Requester`].
+    pub(crate) fn new(forum: Arc<Forum>, users: [(&'i str, Requester); 2]) -> Self {
+        Self {
+            forum,
+            users,
+            style: Style::default(),
+            locale: Locale::default(),
+        }
+    }
+
+    #[inline]
+    /// Set the colors. The layout is ignored.
+    pub(crate) fn set_style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    #[inline]
+    /// Set the language.
+    pub(crate) fn set_locale(mut self, locale: Locale) -> Self {
+        self.locale = locale;
+        self
+    }
+
+    pub(crate) async fn generate(self) -> String {
+        cache::try_init_cache_update_queue().await;
+
+        let [(a, requester_a), (b, requester_b)] = self.users;
+
+        let (a, b) = tokio::join!(
+            get_or_fetch(&self.forum, a, requester_a),
+            get_or_fetch(&self.forum, b, requester_b)
+        );
+
+        self.create([split(a), split(b)])
+    }
+
+    fn create(&self, users: [(Option<Arc<UserInfo>>, Option<FailureKind>); 2]) -> String {
+        let messages = self.locale.messages();
+
+        // From the query, escaped.
+        let names = self.users.map(|(user, _)| ammonia::clean_text(user));
+
+        // Why there's no data, one line for each.
+        let notes = users
+            .iter()
+            .zip(&names)
+            .filter(|((user_info, _), _)| user_info.is_none())
+            .enumerate()
+            .map(|(idx, ((_, failure), name))| {
+                (
+                    r#"
+                <text class="text medium" transform="translate(30 "#,
+                    TABLE_END + 24 * (idx as u16 + 1),
+                    r#")">"#,
+                    name.as_str(),
+                    ": ",
+                    failure.map_or(messages.fetching, |failure| status(failure, messages)),
+                    "</text>",
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let height = TABLE_END + 24 * notes.len() as u16 + 40;
+
+        str_concat!(
+            header(&self.forum, &self.style, "Compare", 600, height),
+            r#"
+                <text class="text" transform="translate(30 50)">"#,
+            messages.compare_title,
+            "</text>",
+            users
+                .iter()
+                .zip(&names)
+                .zip(COLUMNS)
+                .map(|(((user_info, _), name), x)| {
+                    (
+                        user_info
+                            .as_deref()
+                            .map(|user_info| avatar(user_info, x, 36, 20)),
+                        r#"
+                <text class="text medium" x=""#,
+                        x,
+                        r#"" y="80" text-anchor="middle">"#,
+                        user_info
+                            .as_ref()
+                            .map_or(name.as_str(), |user_info| user_info.user.username.as_ref()),
+                        "</text>",
+                    )
+                })
+                .collect::<Vec<_>>(),
+            r#"
+                <line class="line" x1="30" y1="92" x2="570" y2="92"/>"#,
+            Metric::ALL
+                .into_iter()
+                .enumerate()
+                .map(|(idx, metric)| row(metric, &users, 120 + idx as u16 * 30, messages))
+                .collect::<Vec<_>>(),
+            (
+                r#"
+                <line class="line" x1="30" y1=""#,
+                TABLE_END + 2,
+                r#"" x2="570" y2=""#,
+                TABLE_END + 2,
+                r#""/>"#,
+            ),
+            notes,
+            (
+                r#"
+                <text class="text small" transform="translate(30 "#,
+                height - 12,
+                r#")">"#,
+                &self.forum.branding.footer,
+                "</text>",
+            ),
+            r#"
+            </svg>
+            "#
+        )
+    }
+}
+
+/// A row of the table, the higher value is highlighted.
+fn row<'a>(
+    metric: Metric,
+    users: &'a [(Option<Arc<UserInfo>>, Option<FailureKind>); 2],
+    y: u16,
+    messages: &'a Messages,
+) -> impl StringExtT + 'a {
+    let values = users
+        .each_ref()
+        .map(|(user_info, _)| user_info.as_ref().map(|u| metric.value(&u.user_summary)));
+
+    let higher = match values {
+        [Some(a), Some(b)] if a > b => Some(0),
+        [Some(a), Some(b)] if a < b => Some(1),
+        _ => None,
+    };
+
+    (
+        r#"
+                <text class="text" transform="translate(30 "#,
+        y,
+        r#")">"#,
+        metric.label(messages),
+        "</text>",
+        users
+            .iter()
+            .zip(COLUMNS)
+            .enumerate()
+            .map(|(idx, ((user_info, _), x))| {
+                (
+                    r#"
+                <text class="text"#,
+                    (higher == Some(idx)).then_some(" highlight"),
+                    r#"" x=""#,
+                    x,
+                    r#"" y=""#,
+                    y,
+                    r#"" text-anchor="middle">"#,
+                    user_info
+                        .as_ref()
+                        .map(|user_info| metric.format(&user_info.user_summary, messages)),
+                    user_info.is_none().then_some("-"),
+                    "</text>",
+                )
+            })
+            .collect::<Vec<_>>(),
+    )
+}
+
+#[inline]
+/// The cached data, or why there's none.
+fn split(cached: cache::Cached) -> (Option<Arc<UserInfo>>, Option<FailureKind>) {
+    match cached {
+        cache::Cached::Hit(user_info) => (Some(user_info), None),
+        cache::Cached::Failed(kind) => (None, Some(kind)),
+        cache::Cached::Rejected(rejected) => (None, Some(FailureKind::Rejected(rejected))),
+        cache::Cached::Miss => (None, None),
+    }
+}
+
+#[tokio::test]
+async fn test_compare() {
+    let forum = Arc::new(super::upstream::fixture_forum("test-compare"));
+
+    for user in ["hantong", "neo"] {
+        cache::refresh(&forum, user).await.unwrap();
+    }
+    cache::refresh(&forum, "gone").await.unwrap_err();
+
+    let requester = Requester::default();
+
+    let svg = LinuxDoCompareImpl::new(forum.clone(), [("hantong", requester), ("neo", requester)])
+        .generate()
+        .await;
+    // More likes received, fewer solutions
+    assert!(svg.contains(
+        r#"<text class="text highlight" x="330" y="270" text-anchor="middle">2345</text>"#
+    ));
+    assert!(svg.contains(
+        r#"<text class="text highlight" x="480" y="330" text-anchor="middle">20</text>"#
+    ));
+
+    let svg = LinuxDoCompareImpl::new(forum.clone(), [("hantong", requester), ("gone", requester)])
+        .generate()
+        .await;
+    assert!(!svg.contains("highlight\""));
+    assert!(svg.contains(r#"text-anchor="middle">-</text>"#));
+    assert!(svg.contains("gone: ⚠️用户不存在"));
+
+    let svg = LinuxDoCompareImpl::new(
+        forum,
+        [
+            ("hantong", requester),
+            (
+                "<gone>",
+                // Not to consume the fetch quota of other tests
+                Requester {
+                    listed: true,
+                    ..Requester::default()
+                },
+            ),
+        ],
+    )
+    .generate()
+    .await;
+    assert!(svg.contains("&lt;gone&gt;"));
+}
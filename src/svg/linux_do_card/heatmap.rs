@@ -0,0 +1,253 @@
+//! Linux.do activity heatmap, GitHub style
+
+use std::{collections::HashMap, ops::RangeInclusive, sync::Arc};
+
+use chrono::{DateTime, Datelike, Days, NaiveDate, Utc};
+use chrono_tz::Tz;
+use macro_toolset::{str_concat, string::StringExtT};
+
+use super::{cache, model::Activity, policy::Requester, upstream::FailureKind};
+use crate::{
+    config::Forum,
+    svg::locale::{Locale, Messages, fill},
+};
+
+/// Default weeks to show, 26 (half a year)
+const DEFAULT_WEEKS: u8 = 26;
+
+/// Cell size, including the 2px gap
+const CELL: u64 = 12;
+
+#[derive(Debug, Clone)]
+pub(crate) struct LinuxDoHeatmapImpl<'i> {
+    forum: Arc<Forum>,
+    user: &'i str,
+    tz: Tz,
+    weeks: u8,
+    requester: Requester,
+    locale: Locale,
+}
+
+impl<'i> LinuxDoHeatmapImpl<'i> {
+    /// Create a new [`LinuxDoHeatmapImpl`].
+    ///
+    /// `requester`: who is requesting, see [`Requester`].
+    pub(crate) const fn new(
+        forum: Arc<Forum>,
+        user: &'i str,
+        tz: Tz,
+        requester: Requester,
+    ) -> Self {
+        Self {
+            forum,
+            user,
+            tz,
+            weeks: DEFAULT_WEEKS,
+            requester,
+            locale: Locale::ZhCn,
+        }
+    }
+
+    #[inline]
+    /// Weeks to show, 12 ~ 53.
+    pub(crate) fn set_weeks(mut self, weeks: Option<u8>) -> Self {
+        if let Some(weeks) = weeks {
+            self.weeks = weeks.clamp(12, 53);
+        }
+        self
+    }
+
+    #[inline]
+    /// Set the language.
+    pub(crate) fn set_locale(mut self, locale: Locale) -> Self {
+        self.locale = locale;
+        self
+    }
+
+    pub(crate) async fn generate(self) -> String {
+        let (cached, need_fetch) =
+            cache::get_activity_or_fetch(&self.forum, self.user, self.requester);
+
+        if let Some(need_fetch) = need_fetch {
+            need_fetch.await;
+        }
+
+        match cached {
+            cache::Cached::Hit(activity) => self.create(Some(&activity), None),
+            cache::Cached::Failed(kind) => self.create(None, Some(kind)),
+            cache::Cached::Rejected(rejected) => {
+                self.create(None, Some(FailureKind::Rejected(rejected)))
+            }
+            cache::Cached::Miss => self.create(None, None),
+        }
+    }
+
+    fn create(self, activity: Option<&Activity>, failure: Option<FailureKind>) -> String {
+        let today = Utc::now().with_timezone(&self.tz).date_naive();
+
+        // Starts from Sunday
+        let start = today
+            - Days::new(u64::from(today.weekday().num_days_from_sunday()))
+            - Days::new((u64::from(self.weeks) - 1) * 7);
+
+        let counts = count_by_day(
+            activity.iter().flat_map(|activity| &activity.actions),
+            self.tz,
+            start..=today,
+        );
+
+        let total: u64 = counts.values().sum();
+        let max = counts.values().copied().max().unwrap_or_default();
+
+        let cells = start
+            .iter_days()
+            .take_while(|date| *date <= today)
+            .enumerate()
+            .map(|(idx, date)| {
+                let count = counts.get(&date).copied().unwrap_or_default();
+
+                (
+                    (
+                        r#"<rect x=""#,
+                        30 + idx as u64 / 7 * CELL,
+                        r#"" y=""#,
+                        40 + idx as u64 % 7 * CELL,
+                    ),
+                    r#"" width="10" height="10" rx="2" fill=""#,
+                    level_color(count, max),
+                    r#""><title>"#,
+                    date.to_string(),
+                    ": ",
+                    count,
+                    "</title></rect>",
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let width = 60 + u64::from(self.weeks) * CELL;
+
+        str_concat!(
+            r#"
+            <svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 "#,
+            width,
+            r#" 160" fr-init-rc="true">
+                <title>"#,
+            &self.forum.branding.display_name,
+            r#" Heatmap</title>
+                <defs>
+                    <style>
+                        svg { background-color: rgba(0, 0, 0, 0); }
+                        .text { font-size: 12px; fill: rgba(0, 140, 255, 1); font-weight: lighter; }
+                    </style>
+                </defs>
+                <text class="text" transform="translate(30 25)">"#,
+            summary(activity, failure, total, self.weeks, self.locale.messages()),
+            r#"</text>
+                <g id="cells">"#,
+            cells,
+            r#"</g>
+                <g id="legend">
+                    <text class="text" transform="translate(30 145)">"#,
+            self.locale.messages().heatmap_less,
+            "</text>",
+            [0, 1, 2, 3, 4]
+                .into_iter()
+                .enumerate()
+                .map(|(idx, level)| {
+                    (
+                        r#"<rect x=""#,
+                        50 + idx as u64 * CELL,
+                        r#"" y="136" width="10" height="10" rx="2" fill=""#,
+                        level_color(level, 4),
+                        r#""/>"#,
+                    )
+                })
+                .collect::<Vec<_>>(),
+            r#"<text class="text" transform="translate(112 145)">"#,
+            self.locale.messages().heatmap_more,
+            r#"</text>
+                </g>
+            </svg>
+            "#
+        )
+    }
+}
+
+/// Count the actions per day in the given timezone, within the date range.
+fn count_by_day<'a>(
+    actions: impl Iterator<Item = &'a DateTime<Utc>>,
+    tz: Tz,
+    range: RangeInclusive<NaiveDate>,
+) -> HashMap<NaiveDate, u64, foldhash::fast::RandomState> {
+    let mut counts = HashMap::default();
+
+    for action in actions {
+        let date = action.with_timezone(&tz).date_naive();
+
+        if range.contains(&date) {
+            *counts.entry(date).or_default() += 1;
+        }
+    }
+
+    counts
+}
+
+/// The summary line, or why there's no data.
+fn summary(
+    activity: Option<&Activity>,
+    failure: Option<FailureKind>,
+    total: u64,
+    weeks: u8,
+    messages: &Messages,
+) -> impl StringExtT {
+    match (activity, failure) {
+        (Some(_), _) => (
+            Some(fill(messages.heatmap_summary, &[&weeks, &total])),
+            None,
+        ),
+        (None, Some(kind)) => (None, Some(super::status(kind, messages))),
+        (None, None) => (None, Some(messages.fetching)),
+    }
+}
+
+/// Cell color of the count, 5 levels relative to the max.
+const fn level_color(count: u64, max: u64) -> &'static str {
+    if count == 0 || max == 0 {
+        return "rgba(211, 211, 211, 0.4)";
+    }
+
+    match (count * 4).div_ceil(max) {
+        1 => "rgba(0, 140, 255, 0.25)",
+        2 => "rgba(0, 140, 255, 0.5)",
+        3 => "rgba(0, 140, 255, 0.75)",
+        _ => "rgba(0, 140, 255, 1)",
+    }
+}
+
+#[test]
+fn test_count_by_day() {
+    let actions: Vec<DateTime<Utc>> = [
+        "2025-01-01T15:30:00Z",
+        "2025-01-01T16:30:00Z", // 2025-01-02 in Asia/Shanghai
+        "2025-01-02T01:00:00Z",
+        "2024-12-01T00:00:00Z", // Out of range
+    ]
+    .into_iter()
+    .map(|action| action.parse().unwrap())
+    .collect();
+
+    let range = NaiveDate::from_ymd_opt(2024, 12, 25).unwrap()
+        ..=NaiveDate::from_ymd_opt(2025, 1, 2).unwrap();
+
+    let counts = count_by_day(actions.iter(), chrono_tz::Asia::Shanghai, range.clone());
+    assert_eq!(counts.len(), 2);
+    assert_eq!(counts[&NaiveDate::from_ymd_opt(2025, 1, 1).unwrap()], 1);
+    assert_eq!(counts[&NaiveDate::from_ymd_opt(2025, 1, 2).unwrap()], 2);
+
+    let counts = count_by_day(actions.iter(), chrono_tz::UTC, range);
+    assert_eq!(counts[&NaiveDate::from_ymd_opt(2025, 1, 1).unwrap()], 2);
+
+    assert_eq!(level_color(0, 10), level_color(0, 0));
+    assert_eq!(level_color(10, 10), "rgba(0, 140, 255, 1)");
+    assert_eq!(level_color(1, 10), "rgba(0, 140, 255, 0.25)");
+}
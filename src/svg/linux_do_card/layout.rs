@@ -0,0 +1,140 @@
+//! Linux.do cards, layouts and color themes
+
+use std::str::FromStr;
+
+use macro_toolset::string::StringExtT;
+
+use crate::{
+    svg::color::{ColorScheme, DARK, LIGHT, parse_hex_color},
+    utils::Queries,
+};
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    serde::Serialize,
+    serde::Deserialize
+)]
+#[serde(rename_all = "snake_case")]
+/// Card layout
+pub(crate) enum Layout {
+    #[default]
+    /// Everything, 600x270 (600x300 with badges)
+    Full,
+
+    /// Avatar, name and key numbers in a single row, 600x72
+    Compact,
+
+    /// Avatar and name only, 360x48
+    Minimal,
+}
+
+impl FromStr for Layout {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "full" => Ok(Self::Full),
+            "compact" => Ok(Self::Compact),
+            "minimal" => Ok(Self::Minimal),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+/// Card style: layout, color scheme and accent color
+pub(crate) struct Style {
+    pub layout: Layout,
+
+    pub scheme: ColorScheme,
+
+    /// Accent color, validated `#RGB`, `#RRGGBB` or `#RRGGBBAA`
+    pub accent: Option<String>,
+}
+
+impl Style {
+    /// Parse from `layout=`, `theme=` and `accent=` (hex color, `#` is
+    /// optional). Invalid values are ignored.
+    pub(crate) fn from_queries(queries: &Queries) -> Self {
+        Self {
+            layout: queries
+                .get("layout")
+                .and_then(|layout| layout.parse().ok())
+                .unwrap_or_default(),
+            scheme: queries
+                .get("theme")
+                .and_then(|theme| theme.parse().ok())
+                .unwrap_or_default(),
+            accent: queries
+                .get("accent")
+                .and_then(|accent| parse_hex_color(accent)),
+        }
+    }
+
+    /// The CSS of the card.
+    pub(crate) fn css(&self) -> impl StringExtT + '_ {
+        let palette = match self.scheme {
+            ColorScheme::Dark => &DARK,
+            ColorScheme::Auto | ColorScheme::Light => &LIGHT,
+        };
+
+        (
+            r#"
+                        svg { background-color: rgba(0, 0, 0, 0); }
+                        .text { font-size: 16px; fill: "#,
+            self.accent.as_deref().unwrap_or(palette.accent),
+            r#"; font-weight: lighter; }
+                        .medium { font-size: 14px; }
+                        .small { font-size: 10px; }
+                        .highlight { font-weight: bold; }
+                        .line { stroke: "#,
+            palette.line,
+            r#"; stroke-width: 1; }
+                        .bg { fill: "#,
+            palette.background,
+            r#"; }
+                        .avatar .text { fill: rgba(255, 255, 255, 1); text-anchor: middle; }"#,
+            (self.scheme == ColorScheme::Auto).then(|| {
+                (
+                    r#"
+                        @media (prefers-color-scheme: dark) {"#,
+                    self.accent.is_none().then_some((
+                        "\n                            .text { fill: ",
+                        DARK.accent,
+                        "; }",
+                    )),
+                    "\n                            .line { stroke: ",
+                    DARK.line,
+                    "; }\n                        }",
+                )
+            }),
+        )
+    }
+
+    /// The background, none for [`ColorScheme::Auto`].
+    pub(crate) fn background(&self) -> Option<&'static str> {
+        (self.scheme != ColorScheme::Auto)
+            .then_some(r#"<rect class="bg" width="100%" height="100%" rx="6"/>"#)
+    }
+}
+
+#[test]
+fn test_style() {
+    let queries = Queries::try_parse("layout=compact&theme=dark&accent=%23ff8800");
+    let style = Style::from_queries(&queries);
+    assert_eq!(style.layout, Layout::Compact);
+    assert_eq!(style.scheme, ColorScheme::Dark);
+    assert_eq!(style.accent.as_deref(), Some("#ff8800"));
+
+    // Invalid ones are ignored
+    let queries = Queries::try_parse("layout=huge&theme=red&accent=red%3B%7Dsvg%7B");
+    let style = Style::from_queries(&queries);
+    assert_eq!(style.layout, Layout::Full);
+    assert_eq!(style.scheme, ColorScheme::Auto);
+    assert_eq!(style.accent, None);
+}
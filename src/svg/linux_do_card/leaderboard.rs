@@ -0,0 +1,389 @@
+//! Linux.do leaderboard, top members among `leaderboard` of the forum
+
+use std::{str::FromStr, sync::Arc};
+
+use macro_toolset::{str_concat, string::StringExtT};
+
+use super::{
+    avatar, cache, cal_time_delta, duration_human_format, header,
+    layout::{Layout, Style},
+    model::{UserInfo, UserSummary},
+    policy::Requester,
+};
+use crate::{
+    config::Forum,
+    svg::locale::{Locale, Messages, fill},
+};
+
+/// Default users to show, 10
+const DEFAULT_COUNT: u8 = 10;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// Metric to rank by, named after the fields of [`UserSummary`]
+pub(crate) enum Metric {
+    #[default]
+    /// 已收到赞
+    LikesReceived,
+
+    /// 已送出赞
+    LikesGiven,
+
+    /// 访问天数
+    DaysVisited,
+
+    /// 解决方案
+    SolvedCount,
+
+    /// 创建帖子
+    PostCount,
+
+    /// 浏览话题
+    TopicsEntered,
+
+    /// 已读帖子
+    PostsReadCount,
+
+    /// 阅读时间
+    TimeRead,
+}
+
+impl FromStr for Metric {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "likes_received" => Ok(Self::LikesReceived),
+            "likes_given" => Ok(Self::LikesGiven),
+            "days_visited" => Ok(Self::DaysVisited),
+            "solved_count" => Ok(Self::SolvedCount),
+            "post_count" => Ok(Self::PostCount),
+            "topics_entered" => Ok(Self::TopicsEntered),
+            "posts_read_count" => Ok(Self::PostsReadCount),
+            "time_read" => Ok(Self::TimeRead),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Metric {
+    /// All metrics, in the order of the card.
+    pub(super) const ALL: [Self; 8] = [
+        Self::DaysVisited,
+        Self::TimeRead,
+        Self::TopicsEntered,
+        Self::PostsReadCount,
+        Self::LikesGiven,
+        Self::LikesReceived,
+        Self::PostCount,
+        Self::SolvedCount,
+    ];
+
+    #[inline]
+    pub(super) const fn value(self, user_summary: &UserSummary) -> u64 {
+        match self {
+            Self::LikesReceived => user_summary.likes_received,
+            Self::LikesGiven => user_summary.likes_given,
+            Self::DaysVisited => user_summary.days_visited,
+            Self::SolvedCount => user_summary.solved_count,
+            Self::PostCount => user_summary.post_count,
+            Self::TopicsEntered => user_summary.topics_entered,
+            Self::PostsReadCount => user_summary.posts_read_count,
+            Self::TimeRead => user_summary.time_read,
+        }
+    }
+
+    #[inline]
+    /// Label, the same as the card's.
+    pub(super) const fn label(self, messages: &Messages) -> &'static str {
+        messages.metrics[match self {
+            Self::DaysVisited => 0,
+            Self::TimeRead => 1,
+            Self::TopicsEntered => 2,
+            Self::PostsReadCount => 3,
+            Self::LikesGiven => 4,
+            Self::LikesReceived => 5,
+            Self::PostCount => 6,
+            Self::SolvedCount => 7,
+        }]
+    }
+
+    /// The value, formatted like the card does.
+    pub(super) fn format(self, user_summary: &UserSummary, messages: &Messages) -> impl StringExtT {
+        let value = self.value(user_summary);
+
+        match self {
+            Self::TimeRead => (Some(duration_human_format(value, messages)), None),
+            _ => (None, Some(value)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct LinuxDoLeaderboardImpl {
+    forum: Arc<Forum>,
+    metric: Metric,
+    count: u8,
+    style: Style,
+    locale: Locale,
+}
+
+impl LinuxDoLeaderboardImpl {
+    /// Create a new [`LinuxDoLeaderboardImpl`].
+    pub(crate) fn new(forum: Arc<Forum>) -> Self {
+        Self {
+            forum,
+            metric: Metric::default(),
+            count: DEFAULT_COUNT,
+            style: Style::default(),
+            locale: Locale::default(),
+        }
+    }
+
+    #[inline]
+    /// Metric to rank by.
+    pub(crate) fn set_metric(mut self, metric: Option<Metric>) -> Self {
+        if let Some(metric) = metric {
+            self.metric = metric;
+        }
+        self
+    }
+
+    #[inline]
+    /// Users to show, 1 ~ 50.
+    pub(crate) fn set_count(mut self, count: Option<u8>) -> Self {
+        if let Some(count) = count {
+            self.count = count.clamp(1, 50);
+        }
+        self
+    }
+
+    #[inline]
+    /// Set the layout and colors. [`Layout::Minimal`] is the same as
+    /// [`Layout::Compact`] here.
+    pub(crate) fn set_style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    #[inline]
+    /// Set the language.
+    pub(crate) fn set_locale(mut self, locale: Locale) -> Self {
+        self.locale = locale;
+        self
+    }
+
+    pub(crate) async fn generate(self) -> String {
+        cache::try_init_cache_update_queue().await;
+
+        let requester = Requester {
+            listed: true,
+            ..Requester::default()
+        };
+
+        let mut ranked = Vec::with_capacity(self.forum.leaderboard.len());
+        let mut pending = 0;
+
+        for user in &self.forum.leaderboard {
+            let (cached, need_fetch) = cache::get_cache_or_fetch(&self.forum, user, requester);
+
+            if let Some(need_fetch) = need_fetch {
+                need_fetch.await;
+            }
+
+            match cached {
+                cache::Cached::Hit(user_info) => ranked.push(user_info),
+                cache::Cached::Miss => pending += 1,
+                // Not found, etc., just skip.
+                cache::Cached::Failed(_) | cache::Cached::Rejected(_) => {}
+            }
+        }
+
+        ranked.sort_by(|a, b| {
+            self.metric
+                .value(&b.user_summary)
+                .cmp(&self.metric.value(&a.user_summary))
+                .then_with(|| a.user.username.cmp(&b.user.username))
+        });
+        ranked.truncate(usize::from(self.count));
+
+        self.create(&ranked, pending)
+    }
+
+    fn create(&self, ranked: &[Arc<UserInfo>], pending: usize) -> String {
+        let messages = self.locale.messages();
+
+        let row_height: u16 = match self.style.layout {
+            Layout::Full => 48,
+            Layout::Compact | Layout::Minimal => 28,
+        };
+
+        let height = 80 + row_height * (ranked.len().max(1) as u16);
+
+        str_concat!(
+            header(&self.forum, &self.style, "Leaderboard", 600, height),
+            r#"
+                <text class="text" transform="translate(30 30)">"#,
+            fill(messages.leaderboard_title, &[&self.metric.label(messages)]),
+            r#"</text>
+                <line class="line" x1="30" y1="42" x2="570" y2="42"/>"#,
+            ranked
+                .iter()
+                .enumerate()
+                .map(|(idx, user_info)| self.row(idx, user_info, 50 + idx as u16 * row_height))
+                .collect::<Vec<_>>(),
+            ranked.is_empty().then_some((
+                r#"
+                <text class="text" transform="translate(30 70)">"#,
+                if pending > 0 {
+                    messages.fetching
+                } else {
+                    messages.no_data
+                },
+                "</text>",
+            )),
+            (
+                r#"
+                <line class="line" x1="30" y1=""#,
+                height - 30,
+                r#"" x2="570" y2=""#,
+                height - 30,
+                r#""/>
+                <text class="text small" transform="translate(30 "#,
+                height - 12,
+                r#")">"#,
+                &self.forum.branding.footer,
+                "</text>",
+            ),
+            (pending > 0 && !ranked.is_empty()).then(|| {
+                (
+                    r#"
+                <text class="text small" x="570" y=""#,
+                    height - 12,
+                    r#"" text-anchor="end">"#,
+                    fill(messages.pending.of(pending as u64), &[&pending]),
+                    "</text>",
+                )
+            }),
+            r#"
+            </svg>
+            "#
+        )
+    }
+
+    /// A row of the table, starting from `y`.
+    fn row<'a>(&self, idx: usize, user_info: &'a UserInfo, y: u16) -> impl StringExtT + 'a {
+        let rank = match idx {
+            0 => ("🥇", None),
+            1 => ("🥈", None),
+            2 => ("🥉", None),
+            _ => ("#", Some(idx + 1)),
+        };
+        let messages = self.locale.messages();
+        let value = self.metric.format(&user_info.user_summary, messages);
+
+        match self.style.layout {
+            Layout::Full => (
+                Some((
+                    (
+                        r#"
+                <text class="text" transform="translate(30 "#,
+                        y + 30,
+                        r#")">"#,
+                        rank,
+                        "</text>",
+                    ),
+                    avatar(user_info, 84, y + 24, 18),
+                    (
+                        r#"
+                <text class="text" transform="translate(112 "#,
+                        y + 20,
+                        r#")">"#,
+                        &user_info.user.username,
+                        user_info
+                            .user
+                            .name
+                            .as_ref()
+                            .map(|name| (" · ", name.as_ref())),
+                        "</text>",
+                    ),
+                    (
+                        r#"
+                <text class="text small" transform="translate(112 "#,
+                        y + 38,
+                        r#")">"#,
+                        messages.last_seen,
+                        " ",
+                        cal_time_delta(user_info.user.last_seen_at, messages),
+                        "</text>",
+                    ),
+                    (
+                        r#"
+                <text class="text" x="570" y=""#,
+                        y + 30,
+                        r#"" text-anchor="end">"#,
+                        value,
+                        "</text>",
+                    ),
+                )),
+                None,
+            ),
+            Layout::Compact | Layout::Minimal => (
+                None,
+                Some((
+                    (
+                        r#"
+                <text class="text medium" transform="translate(30 "#,
+                        y + 18,
+                        r#")">"#,
+                        rank,
+                        "</text>",
+                    ),
+                    (
+                        r#"
+                <text class="text medium" transform="translate(80 "#,
+                        y + 18,
+                        r#")">"#,
+                        &user_info.user.username,
+                        "</text>",
+                    ),
+                    (
+                        r#"
+                <text class="text medium" x="570" y=""#,
+                        y + 18,
+                        r#"" text-anchor="end">"#,
+                        value,
+                        "</text>",
+                    ),
+                )),
+            ),
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_leaderboard() {
+    assert_eq!("time_read".parse(), Ok(Metric::TimeRead));
+    assert_eq!("likes".parse::<Metric>(), Err(()));
+
+    let forum = Arc::new(Forum {
+        leaderboard: vec![Arc::from("hantong"), Arc::from("gone")],
+        ..super::upstream::fixture_forum("test-leaderboard")
+    });
+
+    cache::refresh(&forum, "hantong").await.unwrap();
+    cache::refresh(&forum, "gone").await.unwrap_err();
+
+    let svg = LinuxDoLeaderboardImpl::new(forum.clone()).generate().await;
+    assert!(svg.contains("🏆 👍已收到赞 排行榜"));
+    assert!(svg.contains("🥇"));
+    assert!(svg.contains(r#"text-anchor="end">2345</text>"#));
+    assert!(!svg.contains("gone"));
+    assert!(!svg.contains("获取中"));
+
+    let svg = LinuxDoLeaderboardImpl::new(forum)
+        .set_metric(Some(Metric::DaysVisited))
+        .set_count(Some(0))
+        .generate()
+        .await;
+    assert!(svg.contains(r#"text-anchor="end">380</text>"#));
+}
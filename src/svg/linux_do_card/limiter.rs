@@ -0,0 +1,322 @@
+//! Linux.do cards, upstream rate limiter
+//!
+//! - Token bucket per forum (host), honouring `Retry-After`.
+//! - Circuit breaker per forum, stop hitting upstream for a while after
+//!   repeated failures.
+//! - Exponential backoff with jitter per failed user.
+
+use std::{
+    sync::{Arc, LazyLock},
+    time::{Duration, Instant},
+};
+
+use dashmap::DashMap;
+use parking_lot::Mutex;
+use rand::Rng;
+
+use super::cache::CacheKey;
+use crate::config::{Forum, RateLimit};
+
+/// Lowest refill rate (tokens per second), in case of an invalid `per_second`
+const MIN_PER_SECOND: f64 = 0.001;
+
+/// Desired max users in the backoff state, expired ones are pruned beyond
+const DESIRED_MAX_BACKOFF_USERS: usize = 5120;
+
+/// Longest time the circuit breaker stays open, 1 day
+const MAX_COOLDOWN: Duration = Duration::from_secs(24 * 3600);
+
+/// Limiters of each forum
+static LIMITERS: LazyLock<DashMap<Arc<str>, Arc<Limiter>, foldhash::fast::RandomState>> =
+    LazyLock::new(DashMap::default);
+
+/// Backoff state of failed users: (user, target) -> (failures, retry after)
+type UserBackoff = DashMap<(CacheKey, Target), (u32, Instant), foldhash::fast::RandomState>;
+
+/// Backoff state of failed users
+static USER_BACKOFF: LazyLock<UserBackoff> = LazyLock::new(DashMap::default);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// What is fetched of the user, backing off separately
+pub(super) enum Target {
+    /// The user card
+    Card,
+
+    /// The activity, see [`fetch_activity`](super::upstream::fetch_activity)
+    Activity,
+}
+
+#[derive(Debug, Clone, thiserror::Error)]
+/// Rejected by the limiter, no request is sent.
+pub(super) enum Rejected {
+    #[error("circuit breaker of forum `{0}` is open, retry after {1:?}")]
+    /// The circuit breaker is open
+    CircuitOpen(Arc<str>, Duration),
+
+    #[error("user `{0}` failed recently, retry after {1:?}")]
+    /// The user is backing off
+    BackingOff(Arc<str>, Duration),
+}
+
+#[derive(Debug)]
+/// Token bucket with a circuit breaker.
+pub(super) struct Limiter {
+    state: Mutex<LimiterState>,
+}
+
+#[derive(Debug)]
+struct LimiterState {
+    /// Available tokens, negative means reserved by waiting requests.
+    tokens: f64,
+
+    /// Last refill time
+    last_refill: Instant,
+
+    /// Blocked until, by `Retry-After`
+    blocked_until: Option<Instant>,
+
+    /// Consecutive failures
+    failures: u32,
+
+    /// Circuit breaker open until
+    open_until: Option<Instant>,
+}
+
+impl Limiter {
+    /// Get the limiter of given forum.
+    pub(super) fn of(forum: &Forum) -> Arc<Self> {
+        LIMITERS
+            .entry(forum.name.clone())
+            .or_insert_with(|| Arc::new(Self::new(&forum.rate_limit)))
+            .clone()
+    }
+
+    fn new(rate_limit: &RateLimit) -> Self {
+        Self {
+            state: Mutex::new(LimiterState {
+                tokens: rate_limit.burst as f64,
+                last_refill: Instant::now(),
+                blocked_until: None,
+                failures: 0,
+                open_until: None,
+            }),
+        }
+    }
+
+    /// Acquire a token, waiting if necessary.
+    ///
+    /// Returns error immediately if the circuit breaker is open.
+    pub(super) async fn acquire(&self, forum: &Forum) -> Result<(), Rejected> {
+        let rate_limit = &forum.rate_limit;
+
+        // NaN is replaced as well.
+        let per_second = rate_limit.per_second.max(MIN_PER_SECOND);
+
+        let to_wait = {
+            let mut state = self.state.lock();
+            let now = Instant::now();
+
+            if let Some(open_until) = state.open_until {
+                if open_until > now {
+                    return Err(Rejected::CircuitOpen(forum.name.clone(), open_until - now));
+                }
+
+                // Half open, let requests go and see what happens.
+                tracing::info!(forum = forum.name.as_ref(), "Circuit breaker half open");
+                state.open_until = None;
+            }
+
+            // Refill
+            let elapsed = now.duration_since(state.last_refill).as_secs_f64();
+            state.tokens = (state.tokens + elapsed * per_second).min(rate_limit.burst as f64);
+            state.last_refill = now;
+
+            // Reserve one
+            state.tokens -= 1.0;
+
+            let token_wait = if state.tokens < 0.0 {
+                Duration::try_from_secs_f64(-state.tokens / per_second).unwrap_or_default()
+            } else {
+                Duration::ZERO
+            };
+
+            let blocked_wait = state
+                .blocked_until
+                .map(|blocked_until| blocked_until.saturating_duration_since(now))
+                .unwrap_or_default();
+
+            token_wait.max(blocked_wait)
+        };
+
+        if !to_wait.is_zero() {
+            tracing::trace!(
+                forum = forum.name.as_ref(),
+                "Initiative request rate limitation exceeded, wait {to_wait:?}"
+            );
+            tokio::time::sleep(to_wait).await;
+        }
+
+        Ok(())
+    }
+
+    /// Report a successful request (upstream is healthy).
+    pub(super) fn on_success(&self) {
+        let mut state = self.state.lock();
+
+        state.failures = 0;
+        state.blocked_until = None;
+    }
+
+    /// Report an upstream failure (429, 5xx, timeout, etc.), with optional
+    /// `Retry-After`.
+    ///
+    /// `Retry-After` is capped by `breaker_cooldown`, never blocking longer
+    /// than the circuit breaker does.
+    pub(super) fn on_failure(&self, forum: &Forum, retry_after: Option<Duration>) {
+        let mut state = self.state.lock();
+        let now = Instant::now();
+
+        let cooldown = Duration::from_secs(forum.rate_limit.breaker_cooldown).min(MAX_COOLDOWN);
+        let retry_after = retry_after.map(|retry_after| retry_after.min(cooldown));
+
+        state.failures = state.failures.saturating_add(1);
+
+        if let Some(blocked_until) =
+            retry_after.and_then(|retry_after| now.checked_add(retry_after))
+        {
+            state.blocked_until = Some(
+                state
+                    .blocked_until
+                    .map_or(blocked_until, |prev| prev.max(blocked_until)),
+            );
+        }
+
+        if state.failures >= forum.rate_limit.breaker_threshold {
+            tracing::warn!(
+                forum = forum.name.as_ref(),
+                failures = state.failures,
+                "Too many upstream failures, circuit breaker open for {cooldown:?}"
+            );
+
+            state.open_until = now.checked_add(cooldown);
+        }
+    }
+}
+
+/// Check if the given user is backing off.
+pub(super) fn check_user_backoff(
+    forum: &Forum,
+    user: &Arc<str>,
+    target: Target,
+) -> Result<(), Rejected> {
+    if let Some(backoff) = USER_BACKOFF.get(&(CacheKey::new(forum, user), target)) {
+        let now = Instant::now();
+
+        if backoff.1 > now {
+            return Err(Rejected::BackingOff(user.clone(), backoff.1 - now));
+        }
+    }
+
+    Ok(())
+}
+
+/// Record a failed fetch of the given user, returns the backoff duration.
+pub(super) fn on_user_failure(forum: &Forum, user: &str, target: Target) -> Duration {
+    if USER_BACKOFF.len() > DESIRED_MAX_BACKOFF_USERS {
+        let now = Instant::now();
+        USER_BACKOFF.retain(|_, backoff| backoff.1 > now);
+    }
+
+    let mut backoff = USER_BACKOFF
+        .entry((CacheKey::new(forum, user), target))
+        .or_insert((0, Instant::now()));
+
+    backoff.0 = backoff.0.saturating_add(1);
+
+    let delay = backoff_delay(&forum.rate_limit, backoff.0);
+    backoff.1 = Instant::now() + delay;
+
+    delay
+}
+
+/// Clear the backoff state of the given user.
+pub(super) fn on_user_success(forum: &Forum, user: &str, target: Target) {
+    USER_BACKOFF.remove(&(CacheKey::new(forum, user), target));
+}
+
+/// Exponential backoff with jitter: `base * 2^(failures - 1)`, capped by max,
+/// then randomized to `[50%, 100%]`.
+fn backoff_delay(rate_limit: &RateLimit, failures: u32) -> Duration {
+    let delay = rate_limit
+        .backoff_base
+        .saturating_mul(
+            1u64.checked_shl(failures.saturating_sub(1))
+                .unwrap_or(u64::MAX),
+        )
+        .min(rate_limit.backoff_max);
+
+    Duration::from_secs(delay).mul_f64(rand::rng().random_range(0.5..=1.0))
+}
+
+#[tokio::test]
+async fn test_limiter() {
+    let forum = Forum {
+        name: Arc::from("test-limiter"),
+        base_url: Arc::from("http://127.0.0.1"),
+        rate_limit: RateLimit {
+            per_second: 20.0,
+            burst: 2,
+            backoff_base: 1,
+            backoff_max: 4,
+            breaker_threshold: 2,
+            breaker_cooldown: 60,
+        },
+        branding: crate::config::ForumBranding::default(),
+        avatar_size: Forum::default_avatar_size(),
+        avatar_hosts: Vec::new(),
+        connect_secret: None,
+        fixtures: None,
+        leaderboard: Vec::new(),
+    };
+
+    let limiter = Limiter::new(&forum.rate_limit);
+
+    // Burst, then 50ms per request.
+    let start = Instant::now();
+    for _ in 0..4 {
+        limiter.acquire(&forum).await.unwrap();
+    }
+    assert!(start.elapsed() >= Duration::from_millis(90));
+
+    // Retry-After honoured
+    limiter.on_failure(&forum, Some(Duration::from_millis(200)));
+    let start = Instant::now();
+    limiter.acquire(&forum).await.unwrap();
+    assert!(start.elapsed() >= Duration::from_millis(190));
+
+    // Bogus `Retry-After` is capped, and never panics.
+    let bogus = Limiter::new(&forum.rate_limit);
+    bogus.on_failure(&forum, Some(Duration::MAX));
+    let blocked_until = bogus.state.lock().blocked_until.unwrap();
+    assert!(blocked_until <= Instant::now() + Duration::from_secs(60));
+
+    // Circuit breaker
+    limiter.on_failure(&forum, None);
+    assert!(matches!(
+        limiter.acquire(&forum).await,
+        Err(Rejected::CircuitOpen(..))
+    ));
+
+    // Backoff
+    let user: Arc<str> = Arc::from("test");
+    for failures in 1..=5 {
+        let delay = on_user_failure(&forum, &user, Target::Card);
+        let expected = Duration::from_secs((1 << (failures - 1)).min(4));
+        assert!(delay >= expected / 2 && delay <= expected);
+    }
+    check_user_backoff(&forum, &user, Target::Card).unwrap_err();
+    check_user_backoff(&forum, &Arc::from("TEST"), Target::Card).unwrap_err();
+    check_user_backoff(&forum, &user, Target::Activity).unwrap();
+    on_user_success(&forum, &user, Target::Card);
+    check_user_backoff(&forum, &user, Target::Card).unwrap();
+}
@@ -5,11 +5,27 @@ use std::{sync::Arc, time::Instant};
 use chrono::{DateTime, Utc};
 
 #[derive(Debug, Clone, Default)]
+#[derive(serde::Serialize, serde::Deserialize)]
 /// Just wrapper over [`User`] and [`UserSummary`]
 pub(super) struct UserInfo {
+    #[serde(skip)]
+    /// When the data is fetched, `None` means not fetched yet.
     pub created: Option<Instant>,
+
+    /// Wall-clock time when the data is fetched, since [`Instant`] can't be
+    /// persisted.
+    pub fetched_at: DateTime<Utc>,
+
     pub user: User,
     pub user_summary: UserSummary,
+
+    #[serde(default)]
+    /// Avatar, as data URI. `None` if not fetched or fetch failed.
+    pub avatar: Option<Arc<str>>,
+
+    #[serde(default)]
+    /// Top badges
+    pub badges: Vec<Badge>,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -23,7 +39,7 @@ pub(super) struct UserSummaryAll {
 }
 
 #[derive(Debug, Clone, Default)]
-#[derive(serde::Deserialize)]
+#[derive(serde::Serialize, serde::Deserialize)]
 #[serde(default)]
 /// <https://linux.do/u/{$username}/summary.json> -> `user_summary`
 pub(super) struct UserSummary {
@@ -63,7 +79,7 @@ pub(super) struct UserAll {
 }
 
 #[derive(Debug, Clone, Default)]
-#[derive(serde::Deserialize)]
+#[derive(serde::Serialize, serde::Deserialize)]
 #[serde(default)]
 /// <https://linux.do/u/{$username}.json> -> user
 pub(super) struct User {
@@ -76,6 +92,15 @@ pub(super) struct User {
     /// 用户昵称
     pub name: Option<Arc<str>>,
 
+    /// 头衔
+    pub title: Option<Arc<str>>,
+
+    /// 群组徽章 (Flair) 名称
+    pub flair_name: Option<Arc<str>>,
+
+    /// 头像模板, e.g. `/user_avatar/linux.do/{username}/{size}/1_2.png`
+    pub avatar_template: Option<Arc<str>>,
+
     /// 用户等级
     pub trust_level: u8,
 
@@ -89,6 +114,59 @@ pub(super) struct User {
     pub created_at: DateTime<Utc>,
 }
 
+#[derive(Debug, Clone, Default)]
+#[derive(serde::Deserialize)]
+#[serde(default)]
+/// <https://linux.do/user-badges/{$username}.json>
+pub(super) struct UserBadgesAll {
+    /// 用户获得的徽章
+    pub badges: Vec<Badge>,
+}
+
+#[derive(Debug, Clone, Default)]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+/// <https://linux.do/user-badges/{$username}.json> -> badges
+pub(super) struct Badge {
+    /// 徽章 ID
+    pub id: u64,
+
+    /// 徽章名称
+    pub name: Arc<str>,
+
+    /// 徽章类型: 1 金, 2 银, 3 铜
+    pub badge_type_id: u8,
+}
+
+#[derive(Debug, Clone, Default)]
+/// User activity: when each topic or reply was created.
+pub(super) struct Activity {
+    /// When the data is fetched, `None` means not fetched yet.
+    pub created: Option<Instant>,
+
+    /// Creation time of the actions
+    pub actions: Vec<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Default)]
+#[derive(serde::Deserialize)]
+#[serde(default)]
+/// <https://linux.do/user_actions.json?username={$username}&filter=4,5>
+pub(super) struct UserActionsAll {
+    /// 用户动态, 新的在前
+    pub user_actions: Vec<UserAction>,
+}
+
+#[derive(Debug, Clone, Default)]
+#[derive(serde::Deserialize)]
+#[serde(default)]
+/// <https://linux.do/user_actions.json?username={$username}&filter=4,5> ->
+/// `user_actions`
+pub(super) struct UserAction {
+    /// 创建时间
+    pub created_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone)]
 #[derive(serde::Deserialize)]
 /// General response for discourse API
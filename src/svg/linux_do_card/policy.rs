@@ -0,0 +1,171 @@
+//! Linux.do cards, which users may be fetched from upstream
+//!
+//! See [`FetchPolicy`].
+
+use std::{
+    sync::LazyLock,
+    time::{Duration, Instant},
+};
+
+use parking_lot::Mutex;
+
+use crate::config::{CONF_FETCH_POLICY, FetchPolicy, Forum};
+
+/// Global fetch quota of [`FetchPolicy::Any`]: (start of the hour, fetches)
+static QUOTA: LazyLock<Mutex<(Instant, u32)>> = LazyLock::new(|| Mutex::new((Instant::now(), 0)));
+
+#[derive(Debug, Clone, Copy, Default)]
+/// Who is requesting the card
+pub(crate) struct Requester {
+    /// Whether a counter with the same id exists
+    pub claimed_counter: bool,
+
+    /// Whether the request is from the forum itself (by `Referer`), only
+    /// trusted by [`FetchPolicy::ClaimedCounterOrReferer`]
+    pub from_forum: bool,
+
+    /// Whether the user is listed in the config (e.g. `leaderboard` of the
+    /// forum), always allowed then.
+    pub listed: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+/// Fetch rejected by [`FetchPolicy`]
+pub(crate) enum Rejected {
+    #[error("user not in the allow-list")]
+    /// [`FetchPolicy::AllowList`]
+    NotAllowed,
+
+    #[error("fetch quota of this hour exhausted")]
+    /// [`FetchPolicy::Any`]
+    QuotaExhausted,
+
+    #[error("no claimed counter")]
+    /// [`FetchPolicy::ClaimedCounter`] or [`FetchPolicy::ClaimedCounterOrReferer`]
+    Unclaimed,
+}
+
+/// Check whether the user may be fetched, consuming the quota if so.
+pub(super) fn check(forum: &Forum, user: &str, requester: Requester) -> Result<(), Rejected> {
+    let result = check_with(&CONF_FETCH_POLICY.load(), &QUOTA, user, requester);
+
+    if let Err(e) = &result {
+        tracing::info!(forum = forum.name.as_ref(), user, "Fetch rejected: {e}");
+    }
+
+    result
+}
+
+/// Check whether the user may be fetched under the given policy, consuming the
+/// given quota if so.
+fn check_with(
+    policy: &FetchPolicy,
+    quota: &Mutex<(Instant, u32)>,
+    user: &str,
+    requester: Requester,
+) -> Result<(), Rejected> {
+    if requester.listed {
+        return Ok(());
+    }
+
+    match policy {
+        FetchPolicy::AllowList { users } => {
+            let user = user.to_lowercase();
+
+            if users.iter().any(|allowed| allowed.to_lowercase() == user) {
+                Ok(())
+            } else {
+                Err(Rejected::NotAllowed)
+            }
+        }
+        FetchPolicy::Any { per_hour } => consume_quota(quota, *per_hour),
+        FetchPolicy::ClaimedCounter => {
+            if requester.claimed_counter {
+                Ok(())
+            } else {
+                Err(Rejected::Unclaimed)
+            }
+        }
+        FetchPolicy::ClaimedCounterOrReferer => {
+            if requester.claimed_counter || requester.from_forum {
+                Ok(())
+            } else {
+                Err(Rejected::Unclaimed)
+            }
+        }
+    }
+}
+
+/// Consume one fetch of the quota of this hour.
+fn consume_quota(quota: &Mutex<(Instant, u32)>, per_hour: u32) -> Result<(), Rejected> {
+    let mut quota = quota.lock();
+
+    if quota.0.elapsed() >= Duration::from_secs(3600) {
+        *quota = (Instant::now(), 0);
+    }
+
+    if quota.1 >= per_hour {
+        return Err(Rejected::QuotaExhausted);
+    }
+
+    quota.1 += 1;
+
+    Ok(())
+}
+
+#[test]
+fn test_check() {
+    let quota = Mutex::new((Instant::now(), 0));
+    let anonymous = Requester::default();
+    let claimed = Requester {
+        claimed_counter: true,
+        ..Requester::default()
+    };
+    let listed = Requester {
+        listed: true,
+        ..Requester::default()
+    };
+    let from_forum = Requester {
+        from_forum: true,
+        ..Requester::default()
+    };
+
+    let policy = FetchPolicy::ClaimedCounter;
+    assert_eq!(
+        check_with(&policy, &quota, "a", anonymous),
+        Err(Rejected::Unclaimed)
+    );
+    check_with(&policy, &quota, "a", claimed).unwrap();
+    assert_eq!(
+        check_with(&policy, &quota, "a", from_forum),
+        Err(Rejected::Unclaimed)
+    );
+
+    let policy = FetchPolicy::ClaimedCounterOrReferer;
+    check_with(&policy, &quota, "a", from_forum).unwrap();
+    check_with(&policy, &quota, "a", claimed).unwrap();
+    assert_eq!(
+        check_with(&policy, &quota, "a", anonymous),
+        Err(Rejected::Unclaimed)
+    );
+
+    let policy = FetchPolicy::AllowList {
+        users: vec!["Hantong".into()],
+    };
+    check_with(&policy, &quota, "hantong", anonymous).unwrap();
+    assert_eq!(
+        check_with(&policy, &quota, "a", claimed),
+        Err(Rejected::NotAllowed)
+    );
+    check_with(&policy, &quota, "a", listed).unwrap();
+
+    let policy = FetchPolicy::Any { per_hour: 2 };
+    check_with(&policy, &quota, "a", anonymous).unwrap();
+    check_with(&policy, &quota, "b", anonymous).unwrap();
+    assert_eq!(
+        check_with(&policy, &quota, "c", claimed),
+        Err(Rejected::QuotaExhausted)
+    );
+    check_with(&policy, &quota, "c", listed).unwrap();
+}
@@ -0,0 +1,80 @@
+//! Linux.do cards, profiles set by the card owners after login
+//!
+//! Used when the corresponding query parameter is not given.
+
+use std::sync::{Arc, LazyLock};
+
+use chrono_tz::Tz;
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+
+use super::{cache::CacheKey, layout::Layout};
+use crate::{config::Forum, db::Persistent};
+
+/// Max length (chars) of the bio
+pub(crate) const BIO_MAX_CHARS: usize = 256;
+
+/// All profiles, loaded from persistent storage on init
+static PROFILES: LazyLock<DashMap<CacheKey, Arc<Profile>, foldhash::fast::RandomState>> =
+    LazyLock::new(DashMap::default);
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+/// Card profile
+pub(crate) struct Profile {
+    /// Custom bio, instead of `note=`
+    pub bio: Option<Arc<str>>,
+
+    /// Timezone, instead of `timezone=`
+    pub timezone: Option<Tz>,
+
+    /// Layout, instead of `layout=`
+    pub layout: Option<Layout>,
+}
+
+/// Get the profile of the given user.
+pub(crate) fn get(forum: &Forum, user: &str) -> Option<Arc<Profile>> {
+    PROFILES
+        .get(&CacheKey::new(forum, user))
+        .map(|profile| profile.clone())
+}
+
+/// Set the profile of the given user, persisted.
+pub(crate) async fn set(forum: &Forum, user: &str, profile: Profile) {
+    let key = CacheKey::new(forum, user);
+
+    match serde_json::to_string(&profile) {
+        Ok(data) => {
+            Persistent::profile_write((key.forum.clone(), key.user.clone(), data)).await;
+        }
+        Err(e) => {
+            tracing::error!(key = %key, "Serialize profile error: {e:#?}");
+        }
+    }
+
+    PROFILES.insert(key, Arc::new(profile));
+}
+
+/// Load all profiles from persistent storage.
+pub(super) async fn load() {
+    let entries = match Persistent::profile_load_all().await {
+        Ok(entries) => entries,
+        Err(e) => {
+            tracing::error!("Load profiles from database error: {e:#?}");
+            return;
+        }
+    };
+
+    for (forum, user, data) in entries {
+        match serde_json::from_str(&data) {
+            Ok(profile) => {
+                PROFILES.insert(CacheKey { forum, user }, Arc::new(profile));
+            }
+            Err(e) => {
+                tracing::warn!("Invalid profile of {forum}/{user}: {e}");
+            }
+        }
+    }
+
+    tracing::info!("Loaded {} profiles from database", PROFILES.len());
+}
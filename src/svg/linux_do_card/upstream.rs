@@ -1,208 +1,676 @@
 //! Linux.do cards, upstream API
 
+mod client;
+mod fixture;
+
 use std::{
-    sync::LazyLock,
+    sync::Arc,
     time::{Duration, Instant},
 };
 
 use anyhow::{Context, Result, bail};
-use macro_toolset::str_concat;
-use parking_lot::Mutex;
-use reqwest::Client;
-
-use super::model;
-use crate::utils::{GENERAL_USER_AGENT, ammonia::get_filterd_note};
-
-static CLIENT: LazyLock<Client> = LazyLock::new(|| {
-    Client::builder()
-        .danger_accept_invalid_certs(cfg!(debug_assertions))
-        .danger_accept_invalid_hostnames(cfg!(debug_assertions))
-        // .proxy(reqwest::Proxy::all("http://127.0.0.1:8888").unwrap())
-        .timeout(Duration::from_secs(5))
-        .pool_idle_timeout(Duration::from_secs(120))
-        .tcp_keepalive(Duration::from_secs(120))
-        .http2_keep_alive_while_idle(true)
-        .http2_keep_alive_interval(Duration::from_secs(15))
-        .http2_keep_alive_timeout(Duration::from_secs(12))
-        .user_agent(GENERAL_USER_AGENT)
-        .build()
-        .expect("will not fail here")
-});
-
-// 土法速率限制
-async fn wait(secs: u64) {
-    static GLOBAL_LIMITTER_LAST_ACCESS: LazyLock<Mutex<Option<Instant>>> =
-        LazyLock::new(|| Mutex::new(Some(Instant::now())));
-
-    // Simple rate limitter, burst=1req/{secs}...
-    loop {
-        // Make compiler happy
-        let elasped = GLOBAL_LIMITTER_LAST_ACCESS.lock().map(|v| v.elapsed());
-        if let Some(elasped) = elasped {
-            if let Some(wait) = Duration::from_secs(secs).checked_sub(elasped) {
-                tracing::trace!("Initiative request rate limitation exceeded, wait {wait:?}");
-                tokio::time::sleep(wait).await;
-                continue;
-            }
+use macro_toolset::{str_concat, string::b64_padding, urlencoding_str};
+use reqwest::{
+    StatusCode,
+    header::{CONTENT_TYPE, RETRY_AFTER},
+};
+
+use super::{
+    limiter::{self, Limiter, Target},
+    model,
+    policy::Rejected,
+};
+use crate::{config::Forum, utils::ammonia::get_filterd_note};
+
+#[derive(Debug, Clone, thiserror::Error)]
+/// Upstream is unavailable (rate limited, server error, etc.)
+pub(super) enum Unavailable {
+    #[error("rate limited by upstream, retry after {0:?}")]
+    /// HTTP 429
+    RateLimited(Option<Duration>),
+
+    #[error("upstream server error: {0}")]
+    /// HTTP 5xx
+    ServerError(StatusCode),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+/// Kind of fetch failure
+pub(crate) enum FailureKind {
+    /// User not found
+    NotFound,
+
+    /// Upstream unavailable, rate limited, etc.
+    Unavailable,
+
+    /// Rejected by the fetch policy, no request is sent.
+    Rejected(Rejected),
+}
+
+impl FailureKind {
+    /// Classify the error returned by [`fetch`].
+    pub(super) fn of(error: &anyhow::Error) -> Self {
+        match error.downcast_ref::<Box<model::ErrorDetails>>() {
+            Some(details) if details.error_type == "not_found" => Self::NotFound,
+            _ => Self::Unavailable,
+        }
+    }
+
+    #[inline]
+    /// Negative cache TTL (secs) of this kind.
+    pub(super) const fn ttl(self) -> u64 {
+        match self {
+            Self::NotFound => NEGATIVE_CACHE_TTL_NOT_FOUND,
+            Self::Unavailable => NEGATIVE_CACHE_TTL_UNAVAILABLE,
+            Self::Rejected(_) => 0,
+        }
+    }
+}
+
+#[cfg(not(debug_assertions))]
+/// Negative cache TTL of not found users, 3600s
+const NEGATIVE_CACHE_TTL_NOT_FOUND: u64 = 3600;
+
+#[cfg(debug_assertions)]
+/// Negative cache TTL of not found users, 3600s
+const NEGATIVE_CACHE_TTL_NOT_FOUND: u64 = 60;
+
+#[cfg(not(debug_assertions))]
+/// Negative cache TTL when upstream is unavailable, 60s
+const NEGATIVE_CACHE_TTL_UNAVAILABLE: u64 = 60;
+
+#[cfg(debug_assertions)]
+/// Negative cache TTL when upstream is unavailable, 60s
+const NEGATIVE_CACHE_TTL_UNAVAILABLE: u64 = 10;
+
+/// Max size of API responses, 4 MiB
+const RESPONSE_MAX_BYTES: u64 = 4 * 1024 * 1024;
+
+/// Send GET request to the given API path of the forum, through the limiter.
+///
+/// Upstream failures (timeout, 429, 5xx) are reported to the limiter, while
+/// Discourse errors (e.g. user not found) are returned as
+/// [`model::ErrorDetails`].
+async fn get<T>(forum: &Forum, path: &str) -> Result<T>
+where
+    T: serde::de::DeserializeOwned,
+{
+    let upstream = client::of(forum)?;
+    let limiter = Limiter::of(forum);
+
+    limiter.acquire(forum).await?;
+
+    let response = match upstream.get(path, RESPONSE_MAX_BYTES).await {
+        Ok(response) => response,
+        Err(e) => {
+            limiter.on_failure(forum, None);
+
+            return Err(e).with_context(|| {
+                format!("Fetch response from {}{{{path}}} error", forum.base_url)
+            });
         }
+    };
 
-        *GLOBAL_LIMITTER_LAST_ACCESS.lock() = Some(Instant::now());
-        break;
+    let status = response.status;
+
+    if status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error() {
+        let retry_after = response
+            .headers
+            .get(RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(parse_retry_after);
+
+        limiter.on_failure(forum, retry_after);
+
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            bail!(Unavailable::RateLimited(retry_after))
+        } else {
+            bail!(Unavailable::ServerError(status))
+        }
     }
+
+    limiter.on_success();
+
+    // Discourse returns error details with 4xx status code.
+    let response = serde_json::from_slice::<model::GeneralResponse<T>>(&response.body)
+        .with_context(|| {
+            format!(
+                "Parse json response from {}{{{path}}} error, status: {status}",
+                forum.base_url
+            )
+        })?
+        .result()?;
+
+    Ok(response)
 }
 
-#[tracing::instrument(level = "debug", ret)]
-pub(super) async fn fetch(user_name: &str) -> Result<model::UserInfo> {
+/// Parse `Retry-After` header, delay-seconds or HTTP-date.
+fn parse_retry_after(retry_after: &str) -> Option<Duration> {
+    let retry_after = retry_after.trim();
+
+    retry_after
+        .parse()
+        .map(Duration::from_secs)
+        .ok()
+        .or_else(|| {
+            chrono::DateTime::parse_from_rfc2822(retry_after)
+                .ok()
+                .map(|date| {
+                    (date.to_utc() - chrono::Utc::now())
+                        .to_std()
+                        .unwrap_or_default()
+                })
+        })
+}
+
+#[tracing::instrument(level = "debug", skip(forum), fields(forum = forum.name.as_ref()), ret)]
+pub(super) async fn fetch(forum: &Forum, user_name: &str) -> Result<model::UserInfo> {
     if user_name.is_empty() {
         bail!("Empty user name!")
     }
 
-    wait(1).await;
-    let mut user = CLIENT
-        .get(str_concat!("https://linux.do/u/", user_name, ".json"))
-        .send()
-        .await
-        .context("Fetch response from https://linux.do/u/{$username}.json error")?
-        .error_for_status()
-        .context("Fetch response from https://linux.do/u/{$username}.json status error")?
-        .json::<model::GeneralResponse<model::UserAll>>()
-        .await
-        .context("Parse json response from https://linux.do/u/{$username}.json error")?
-        .result()?
-        .user;
+    let user_name: Arc<str> = Arc::from(user_name);
+
+    with_user_backoff(
+        forum,
+        &user_name,
+        Target::Card,
+        fetch_inner(forum, &user_name),
+    )
+    .await
+}
+
+#[tracing::instrument(level = "debug", skip(forum), fields(forum = forum.name.as_ref()))]
+/// Fetch the activity (topics and replies) of the user, within
+/// [`ACTIVITY_MAX_DAYS`].
+pub(super) async fn fetch_activity(forum: &Forum, user_name: &str) -> Result<model::Activity> {
+    if user_name.is_empty() {
+        bail!("Empty user name!")
+    }
+
+    let user_name: Arc<str> = Arc::from(user_name);
+
+    with_user_backoff(
+        forum,
+        &user_name,
+        Target::Activity,
+        fetch_activity_inner(forum, &user_name),
+    )
+    .await
+}
+
+/// Run the fetch task, honouring and updating the backoff state of the user.
+async fn with_user_backoff<T>(
+    forum: &Forum,
+    user_name: &Arc<str>,
+    target: Target,
+    task: impl Future<Output = Result<T>>,
+) -> Result<T> {
+    limiter::check_user_backoff(forum, user_name, target)?;
+
+    match task.await {
+        Ok(value) => {
+            limiter::on_user_success(forum, user_name, target);
+
+            Ok(value)
+        }
+        Err(e) => {
+            let backoff = limiter::on_user_failure(forum, user_name, target);
+
+            Err(e.context(format!("Backing off for {backoff:?}")))
+        }
+    }
+}
+
+async fn fetch_inner(forum: &Forum, user_name: &str) -> Result<model::UserInfo> {
+    let mut user = get::<model::UserAll>(
+        forum,
+        &str_concat!("/u/", urlencoding_str!(E: user_name), ".json"),
+    )
+    .await?
+    .user;
 
     // 32 中文 (1中文字符 = 2英文字符), 72 英文
     // abcdefgabcdefgabcdefgabcdefgabcdefgabcdefgabcdefgabcdefgabcdefga
 
     //  Filter
-    if let Some(bio_raw) = &user.bio_raw {
-        if let Some(bio_raw) = get_filterd_note(bio_raw, None, false).await {
-            user.bio_raw = Some(bio_raw);
-        }
+    for field in [
+        &mut user.bio_raw,
+        &mut user.name,
+        &mut user.title,
+        &mut user.flair_name,
+    ] {
+        filter(field).await;
     }
 
-    wait(1).await;
-    let user_summary = CLIENT
-        .get(str_concat!(
-            "https://linux.do/u/",
-            user_name,
-            "/summary.json"
-        ))
-        .send()
-        .await
-        .context("Fetch response from https://linux.do/u/{$username}/summary.json error")?
-        .error_for_status()
-        .context("Fetch response from https://linux.do/u/{$username}.json status error")?
-        .json::<model::GeneralResponse<model::UserSummaryAll>>()
-        .await
-        .context("Parse json response from https://linux.do/u/{$username}/summary.json error")?
-        .result()?
-        .user_summary;
+    let user_summary = get::<model::UserSummaryAll>(
+        forum,
+        &str_concat!("/u/", urlencoding_str!(E: user_name), "/summary.json"),
+    )
+    .await?
+    .user_summary;
+
+    // Badges are not that important, just ignore errors.
+    let badges = match get::<model::UserBadgesAll>(
+        forum,
+        &str_concat!(
+            "/user-badges/",
+            urlencoding_str!(E: user_name),
+            ".json?grouped=true"
+        ),
+    )
+    .await
+    {
+        Ok(badges) => top_badges(badges.badges).await,
+        Err(e) => {
+            tracing::warn!(
+                forum = forum.name.as_ref(),
+                "Fetch badges of {user_name} error: {e:#}"
+            );
+            Vec::new()
+        }
+    };
 
     Ok(model::UserInfo {
         created: Some(Instant::now()),
+        fetched_at: chrono::Utc::now(),
         user,
         user_summary,
+        avatar: None,
+        badges,
+    })
+}
+
+/// Activity older than this is not fetched, 371 days (53 weeks)
+pub(super) const ACTIVITY_MAX_DAYS: i64 = 371;
+
+/// Max pages of `user_actions.json` to fetch
+const ACTIVITY_MAX_PAGES: usize = 10;
+
+async fn fetch_activity_inner(forum: &Forum, user_name: &str) -> Result<model::Activity> {
+    let since = chrono::Utc::now() - chrono::TimeDelta::days(ACTIVITY_MAX_DAYS);
+
+    let mut actions = Vec::new();
+
+    for _ in 0..ACTIVITY_MAX_PAGES {
+        // 4: new topic, 5: reply
+        let page = get::<model::UserActionsAll>(
+            forum,
+            &str_concat!(
+                "/user_actions.json?filter=4,5&username=",
+                urlencoding_str!(E: user_name),
+                "&offset=",
+                actions.len()
+            ),
+        )
+        .await?
+        .user_actions;
+
+        let Some(oldest) = page.last().map(|action| action.created_at) else {
+            break;
+        };
+
+        actions.extend(
+            page.into_iter()
+                .map(|action| action.created_at)
+                .filter(|created_at| *created_at >= since),
+        );
+
+        if oldest < since {
+            break;
+        }
+    }
+
+    Ok(model::Activity {
+        created: Some(Instant::now()),
+        actions,
     })
 }
 
-// #[tokio::test]
-// async fn test() {
-//     macro_toolset::init_tracing_simple!();
-
-//     let user = "hantong";
-//     // let data = CLIENT
-//     //     .get(str_concat!("https://linux.do/u/", user, "/summary.json"))
-//     //     .send()
-//     //     .await
-//     //     .unwrap()
-//     //     .text()
-//     //     .await
-//     //     .unwrap();
-
-//     let mut joinset = tokio::task::JoinSet::new();
-
-//     joinset.spawn(async {
-//         fetch(user).await;
-//         println!("OK from thread 1");
-//     });
-
-//     joinset.spawn(async {
-//         fetch(user).await;
-//         println!("OK from thread 2");
-//     });
-
-//     joinset.spawn(async {
-//         fetch(user).await;
-//         println!("OK from thread 3");
-//     });
-
-//     joinset.spawn(async {
-//         fetch(user).await;
-//         println!("OK from thread 4");
-//     });
-
-//     joinset.spawn(async {
-//         fetch(user).await;
-//         println!("OK from thread 5");
-//     });
-
-//     joinset.spawn(async {
-//         fetch(user).await;
-//         println!("OK from thread 6");
-//     });
-
-//     joinset.spawn(async {
-//         fetch(user).await;
-//         println!("OK from thread 7");
-//     });
-
-//     joinset.spawn(async {
-//         fetch(user).await;
-//         println!("OK from thread 8");
-//     });
-
-//     joinset.spawn(async {
-//         fetch(user).await;
-//         println!("OK from thread 9");
-//     });
-
-//     joinset.spawn(async {
-//         fetch(user).await;
-//         println!("OK from thread 10");
-//     });
-
-//     joinset.spawn(async {
-//         fetch(user).await;
-//         println!("OK from thread 11");
-//     });
-
-//     joinset.spawn(async {
-//         fetch(user).await;
-//         println!("OK from thread 12");
-//     });
-
-//     joinset.spawn(async {
-//         fetch(user).await;
-//         println!("OK from thread 13");
-//     });
-
-//     joinset.spawn(async {
-//         fetch(user).await;
-//         println!("OK from thread 14");
-//     });
-//     joinset.spawn(async {
-//         fetch(user).await;
-//         println!("OK from thread 15");
-//     });
-//     joinset.spawn(async {
-//         fetch(user).await;
-//         println!("OK from thread 16");
-//     });
-//     joinset.spawn(async {
-//         fetch(user).await;
-//         println!("OK from thread 17");
-//     });
-
-//     joinset.join_all().await;
-// }
+#[inline]
+/// Filter the text from upstream in place.
+async fn filter(field: &mut Option<Arc<str>>) {
+    if let Some(text) = field {
+        if let Some(filtered) = get_filterd_note(text, None, false).await {
+            *field = Some(filtered);
+        }
+    }
+}
+
+/// Max badges to show
+const TOP_BADGES: usize = 3;
+
+/// Top badges: gold, silver then bronze, at most [`TOP_BADGES`].
+async fn top_badges(mut badges: Vec<model::Badge>) -> Vec<model::Badge> {
+    badges.sort_by_key(|badge| match badge.badge_type_id {
+        type_id @ 1..=3 => type_id,
+        _ => u8::MAX,
+    });
+    badges.truncate(TOP_BADGES);
+
+    for badge in &mut badges {
+        if let Some(name) = get_filterd_note(&badge.name, None, false).await {
+            badge.name = name;
+        }
+    }
+
+    badges
+}
+
+/// Max avatar size, 256 KiB
+const AVATAR_MAX_BYTES: u64 = 256 * 1024;
+
+/// Attach the avatar (as data URI) to the fetched user info.
+///
+/// The previous one is reused if `avatar_template` is unchanged. Failures are
+/// just logged, and the card will draw a placeholder instead.
+pub(super) async fn attach_avatar(
+    forum: &Forum,
+    user_info: &mut model::UserInfo,
+    previous: Option<&model::UserInfo>,
+) {
+    let Some(template) = user_info.user.avatar_template.clone() else {
+        return;
+    };
+
+    if let Some(avatar) = previous
+        .filter(|previous| previous.user.avatar_template.as_ref() == Some(&template))
+        .and_then(|previous| previous.avatar.clone())
+    {
+        user_info.avatar = Some(avatar);
+        return;
+    }
+
+    match fetch_avatar(forum, &template).await {
+        Ok(avatar) => user_info.avatar = Some(avatar),
+        Err(e) => {
+            tracing::warn!(
+                forum = forum.name.as_ref(),
+                "Fetch avatar `{template}` error: {e:#}"
+            );
+        }
+    }
+}
+
+/// Fetch the avatar of given `avatar_template`, returns data URI.
+async fn fetch_avatar(forum: &Forum, template: &str) -> Result<Arc<str>> {
+    // Maybe on CDN, resolved by the upstream.
+    let path = template.replace("{size}", &forum.avatar_size.to_string());
+
+    let upstream = client::of(forum)?;
+
+    Limiter::of(forum).acquire(forum).await?;
+
+    let response = upstream
+        .get(&path, AVATAR_MAX_BYTES)
+        .await
+        .with_context(|| format!("Fetch avatar from {path} error"))?;
+
+    if !response.status.is_success() {
+        bail!(
+            "Fetch avatar from {path} error, status: {}",
+            response.status
+        )
+    }
+
+    // No SVG here, it's to be embedded.
+    let mime = match response
+        .headers
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(';').next())
+        .map(str::trim)
+    {
+        Some(mime @ ("image/png" | "image/jpeg" | "image/gif" | "image/webp")) => mime.to_owned(),
+        mime => bail!("Unsupported avatar content type: {mime:?}"),
+    };
+
+    Ok(Arc::from(str_concat!(
+        "data:",
+        mime,
+        ";base64,",
+        b64_padding::STANDARD::encode(&response.body)
+    )))
+}
+
+#[cfg(test)]
+/// Forum replaying the recorded responses in `fixtures/linux-do.json`, with
+/// the rate limit relaxed, for tests.
+pub(super) fn fixture_forum(name: &str) -> Forum {
+    use crate::config::RateLimit;
+
+    Forum {
+        name: Arc::from(name),
+        rate_limit: RateLimit {
+            per_second: 100.0,
+            burst: 10,
+            ..RateLimit::default()
+        },
+        fixtures: Some("fixtures/linux-do.json".into()),
+        ..Forum::default_list()[0].as_ref().clone()
+    }
+}
+
+#[tokio::test]
+async fn test_upstream_unavailable() {
+    use axum::{http::HeaderValue, response::IntoResponse, routing::get};
+
+    use crate::config::RateLimit;
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        let app = axum::Router::new()
+            .route(
+                "/u/{user}",
+                get(|| async {
+                    (
+                        StatusCode::TOO_MANY_REQUESTS,
+                        [(RETRY_AFTER, HeaderValue::from_static("1"))],
+                    )
+                        .into_response()
+                }),
+            )
+            .route(
+                "/u/{user}/summary.json",
+                get(|| async { StatusCode::BAD_GATEWAY }),
+            );
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    let forum = Forum {
+        base_url: Arc::from(format!("http://{addr}")),
+        rate_limit: RateLimit {
+            breaker_threshold: 2,
+            ..RateLimit::default()
+        },
+        fixtures: None,
+        ..fixture_forum("test-upstream")
+    };
+
+    // `Retry-After` counts from the response, so later than this.
+    let start = Instant::now();
+    let error = fetch(&forum, "test").await.unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<Unavailable>(),
+        Some(Unavailable::RateLimited(Some(_)))
+    ));
+
+    // Backing off
+    let error = fetch(&forum, "test").await.unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<limiter::Rejected>(),
+        Some(limiter::Rejected::BackingOff(..))
+    ));
+
+    // Retry-After honoured, then circuit breaker open
+    let error = fetch(&forum, "test2").await.unwrap_err();
+    assert!(start.elapsed() >= Duration::from_secs(1));
+    assert!(error.downcast_ref::<Unavailable>().is_some());
+
+    let error = fetch(&forum, "test3").await.unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<limiter::Rejected>(),
+        Some(limiter::Rejected::CircuitOpen(..))
+    ));
+}
+
+#[tokio::test]
+async fn test_attach_avatar() {
+    use axum::{extract::Path, http::HeaderValue, routing::get};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        let app = axum::Router::new()
+            .route(
+                "/avatar/{size}/a.png",
+                get(|Path(size): Path<u16>| async move {
+                    assert_eq!(size, 96);
+                    (
+                        [(CONTENT_TYPE, HeaderValue::from_static("image/png"))],
+                        b"\x89PNG".as_slice(),
+                    )
+                }),
+            )
+            .route(
+                "/avatar/{size}/a.svg",
+                get(|| async {
+                    (
+                        [(CONTENT_TYPE, HeaderValue::from_static("image/svg+xml"))],
+                        "<svg/>",
+                    )
+                }),
+            );
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    let forum = Forum {
+        base_url: Arc::from(format!("http://{addr}")),
+        fixtures: None,
+        ..fixture_forum("test-avatar")
+    };
+
+    let mut user_info = model::UserInfo::default();
+    user_info.user.avatar_template = Some(Arc::from("/avatar/{size}/a.png"));
+    attach_avatar(&forum, &mut user_info, None).await;
+    assert_eq!(
+        user_info.avatar.as_deref(),
+        Some("data:image/png;base64,iVBORw==")
+    );
+
+    // Reused if unchanged
+    let mut previous = user_info.clone();
+    previous.avatar = Some(Arc::from("data:image/png;base64,"));
+    attach_avatar(&forum, &mut user_info, Some(&previous)).await;
+    assert_eq!(user_info.avatar, previous.avatar);
+
+    // No SVG
+    let mut user_info = model::UserInfo::default();
+    user_info.user.avatar_template = Some(Arc::from("/avatar/{size}/a.svg"));
+    attach_avatar(&forum, &mut user_info, None).await;
+    assert!(user_info.avatar.is_none());
+    // Not on the forum's host
+    let mut user_info = model::UserInfo::default();
+    user_info.user.avatar_template = Some(Arc::from(format!(
+        "http://localhost:{}/avatar/{{size}}/a.png",
+        addr.port()
+    )));
+    attach_avatar(&forum, &mut user_info, None).await;
+    assert!(user_info.avatar.is_none());
+}
+
+#[tokio::test]
+async fn test_top_badges() {
+    let badges: model::UserBadgesAll = serde_json::from_str(
+        r#"{"badges":[
+            {"id":1,"name":"Basic","badge_type_id":3},
+            {"id":2,"name":"Unknown","badge_type_id":0},
+            {"id":3,"name":"<b>Leader</b>","badge_type_id":1},
+            {"id":4,"name":"Regular","badge_type_id":2},
+            {"id":5,"name":"Member","badge_type_id":3}
+        ]}"#,
+    )
+    .unwrap();
+
+    let badges = top_badges(badges.badges).await;
+    assert_eq!(badges.iter().map(|b| b.id).collect::<Vec<_>>(), [3, 4, 1]);
+    assert_eq!(badges[0].name.as_ref(), "Leader");
+}
+
+#[test]
+fn test_failure_kind() {
+    let not_found: model::GeneralResponse<model::UserAll> = serde_json::from_str(
+        r#"{"errors":["The requested URL or resource could not be found."],"error_type":"not_found"}"#,
+    )
+    .unwrap();
+    let error = anyhow::Error::from(not_found.result().unwrap_err());
+    assert_eq!(FailureKind::of(&error), FailureKind::NotFound);
+
+    let error = anyhow::Error::from(Unavailable::ServerError(StatusCode::BAD_GATEWAY));
+    assert_eq!(FailureKind::of(&error), FailureKind::Unavailable);
+}
+
+#[tokio::test]
+async fn test_fixtures() {
+    let forum = fixture_forum("test-fixtures");
+
+    let mut user_info = fetch(&forum, "hantong").await.unwrap();
+    assert_eq!(user_info.user.username.as_ref(), "hantong");
+    assert_eq!(user_info.user.trust_level, 3);
+    assert_eq!(user_info.user_summary.likes_received, 2345);
+    assert!(
+        !user_info
+            .user
+            .bio_raw
+            .as_deref()
+            .unwrap()
+            .contains("<script")
+    );
+    assert_eq!(
+        user_info.badges.iter().map(|b| b.id).collect::<Vec<_>>(),
+        [102, 3, 1]
+    );
+
+    attach_avatar(&forum, &mut user_info, None).await;
+    assert_eq!(
+        user_info.avatar.as_deref(),
+        Some("data:image/png;base64,UE5H")
+    );
+
+    let activity = fetch_activity(&forum, "hantong").await.unwrap();
+    assert!(activity.actions.len() <= 3);
+
+    // Discourse errors
+    let error = fetch(&forum, "gone").await.unwrap_err();
+    assert_eq!(FailureKind::of(&error), FailureKind::NotFound);
+
+    let error = fetch(&forum, "never-recorded").await.unwrap_err();
+    assert_eq!(FailureKind::of(&error), FailureKind::NotFound);
+
+    let error = fetch(&forum, "hidden").await.unwrap_err();
+    assert_eq!(FailureKind::of(&error), FailureKind::Unavailable);
+    assert_eq!(
+        error
+            .downcast_ref::<Box<model::ErrorDetails>>()
+            .unwrap()
+            .error_type,
+        "invalid_access"
+    );
+
+    // Upstream failures
+    let error = fetch(&forum, "broken").await.unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<Unavailable>(),
+        Some(Unavailable::ServerError(StatusCode::BAD_GATEWAY))
+    ));
+
+    let error = fetch(&forum, "busy").await.unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<Unavailable>(),
+        Some(Unavailable::RateLimited(Some(retry_after))) if *retry_after == Duration::from_secs(1)
+    ));
+}
@@ -0,0 +1,194 @@
+//! Linux.do cards, upstream clients
+//!
+//! Requests are sent over HTTP by default, or replayed from the recorded
+//! fixtures (see `fixtures` of [`Forum`]) for development and tests.
+
+use std::{
+    fmt,
+    pin::Pin,
+    sync::{Arc, LazyLock},
+    time::Duration,
+};
+
+use anyhow::{Context, Result, bail};
+use bytes::{Bytes, BytesMut};
+use dashmap::DashMap;
+use macro_toolset::str_concat;
+use reqwest::{Client, StatusCode, Url, header::HeaderMap};
+
+use super::fixture::Fixtures;
+use crate::{config::Forum, utils::GENERAL_USER_AGENT};
+
+static CLIENT: LazyLock<Client> = LazyLock::new(|| {
+    Client::builder()
+        .danger_accept_invalid_certs(cfg!(debug_assertions))
+        .danger_accept_invalid_hostnames(cfg!(debug_assertions))
+        // .proxy(reqwest::Proxy::all("http://127.0.0.1:8888").unwrap())
+        .timeout(Duration::from_secs(5))
+        .pool_idle_timeout(Duration::from_secs(120))
+        .tcp_keepalive(Duration::from_secs(120))
+        .http2_keep_alive_while_idle(true)
+        .http2_keep_alive_interval(Duration::from_secs(15))
+        .http2_keep_alive_timeout(Duration::from_secs(12))
+        .user_agent(GENERAL_USER_AGENT)
+        .build()
+        .expect("will not fail here")
+});
+
+/// Upstream clients of each forum
+static UPSTREAMS: LazyLock<DashMap<Arc<str>, Arc<dyn Upstream>, foldhash::fast::RandomState>> =
+    LazyLock::new(DashMap::default);
+
+/// Boxed future returned by [`Upstream`]
+pub(super) type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+#[derive(Debug, Clone)]
+/// Upstream response, with the body read.
+pub(super) struct Response {
+    pub status: StatusCode,
+
+    pub headers: HeaderMap,
+
+    pub body: Bytes,
+}
+
+/// Upstream of a forum
+pub(super) trait Upstream: fmt::Debug + Send + Sync {
+    /// Send GET request to the given path, or absolute URL (e.g. avatars on
+    /// CDN).
+    ///
+    /// Fails if the body is larger than `max_bytes`.
+    fn get<'a>(&'a self, path: &'a str, max_bytes: u64) -> BoxFuture<'a, Result<Response>>;
+}
+
+/// Get the upstream of given forum.
+pub(super) fn of(forum: &Forum) -> Result<Arc<dyn Upstream>> {
+    UPSTREAMS
+        .entry(forum.name.clone())
+        .or_try_insert_with(|| -> Result<Arc<dyn Upstream>> {
+            match &forum.fixtures {
+                Some(path) => Ok(Arc::new(Fixtures::load(&forum.base_url, path)?)),
+                None => Ok(Arc::new(Http {
+                    base_url: forum.base_url.clone(),
+                    hosts: allowed_hosts(forum)?,
+                })),
+            }
+        })
+        .map(|upstream| upstream.clone())
+}
+
+#[derive(Debug)]
+/// Requests the forum over HTTP.
+struct Http {
+    base_url: Arc<str>,
+
+    /// Hosts requests may be sent to, see [`allowed_hosts`]
+    hosts: Vec<String>,
+}
+
+impl Upstream for Http {
+    fn get<'a>(&'a self, path: &'a str, max_bytes: u64) -> BoxFuture<'a, Result<Response>> {
+        Box::pin(async move {
+            let url = resolve(&self.base_url, &self.hosts, path)?;
+
+            let mut response = CLIENT.get(&url).send().await?;
+
+            if response.content_length().is_some_and(|len| len > max_bytes) {
+                bail!("Response too large")
+            }
+
+            let status = response.status();
+            let headers = std::mem::take(response.headers_mut());
+
+            let mut body = BytesMut::new();
+            while let Some(chunk) = response.chunk().await.context("Read response error")? {
+                if (body.len() + chunk.len()) as u64 > max_bytes {
+                    bail!("Response too large")
+                }
+
+                body.extend_from_slice(&chunk);
+            }
+
+            Ok(Response {
+                status,
+                headers,
+                body: body.freeze(),
+            })
+        })
+    }
+}
+
+/// Hosts requests of the forum may be sent to: the host of `base_url`, and
+/// `avatar_hosts` (e.g. the CDN).
+fn allowed_hosts(forum: &Forum) -> Result<Vec<String>> {
+    let base_host = Url::parse(&forum.base_url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_owned))
+        .with_context(|| format!("Invalid base URL `{}`", forum.base_url))?;
+
+    Ok(std::iter::once(base_host)
+        .chain(
+            forum
+                .avatar_hosts
+                .iter()
+                .map(|host| host.to_ascii_lowercase()),
+        )
+        .collect())
+}
+
+/// Resolve the path against the base URL. Absolute URLs are kept, and
+/// protocol relative ones (`//`) are HTTPS.
+///
+/// Fails if the host of the resolved URL is not one of the given hosts, so
+/// that upstream can't make us request anything else (e.g. internal
+/// addresses).
+fn resolve(base_url: &str, hosts: &[String], path: &str) -> Result<String> {
+    let url = if path.starts_with("https://") || path.starts_with("http://") {
+        path.to_owned()
+    } else if let Some(path) = path.strip_prefix("//") {
+        str_concat!("https://", path)
+    } else {
+        str_concat!(base_url.trim_end_matches('/'), path)
+    };
+
+    let parsed = Url::parse(&url).with_context(|| format!("Invalid URL `{url}`"))?;
+
+    if !parsed
+        .host_str()
+        .is_some_and(|host| hosts.iter().any(|allowed| allowed == host))
+    {
+        bail!("Host of `{url}` is not allowed")
+    }
+
+    Ok(url)
+}
+
+#[test]
+fn test_resolve() {
+    let hosts = ["linux.do".to_owned(), "cdn.linux.do".to_owned()];
+
+    assert_eq!(
+        resolve("https://linux.do/", &hosts, "/u/a.json").unwrap(),
+        "https://linux.do/u/a.json"
+    );
+    assert_eq!(
+        resolve("https://linux.do", &hosts, "//cdn.linux.do/a.png").unwrap(),
+        "https://cdn.linux.do/a.png"
+    );
+    assert_eq!(
+        resolve("https://linux.do", &hosts, "https://linux.do/a.png").unwrap(),
+        "https://linux.do/a.png"
+    );
+
+    // Loopback and other hosts are rejected
+    for path in [
+        "http://127.0.0.1/a.png",
+        "//localhost/a.png",
+        "https://example.com/a.png",
+        "https://linux.do@127.0.0.1/a.png",
+        "@127.0.0.1/a.png",
+        ".example.com/a.png",
+    ] {
+        resolve("https://linux.do", &hosts, path).unwrap_err();
+    }
+}
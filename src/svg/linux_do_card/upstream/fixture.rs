@@ -0,0 +1,143 @@
+//! Linux.do cards, replaying recorded upstream responses
+//!
+//! The fixture file is a JSON object, from the request path (with query) to
+//! the recorded response:
+//!
+//! ```json
+//! {
+//!     "/u/hantong.json": { "body": { "user": { "username": "hantong" } } },
+//!     "/u/gone.json": {
+//!         "status": 404,
+//!         "body": { "errors": ["..."], "error_type": "not_found" }
+//!     },
+//!     "/u/busy.json": { "status": 429, "headers": { "retry-after": "1" } },
+//!     "/avatar/96/a.png": { "headers": { "content-type": "image/png" }, "body": "PNG" }
+//! }
+//! ```
+//!
+//! JSON bodies are replayed as `application/json`, while string bodies are
+//! replayed as is. Requests not recorded get 404 with Discourse's `not_found`
+//! error, like the forum does for unknown users.
+
+use std::{collections::HashMap, path::Path};
+
+use anyhow::{Context, Result, bail};
+use bytes::Bytes;
+use reqwest::{
+    StatusCode,
+    header::{CONTENT_TYPE, HeaderMap, HeaderName, HeaderValue},
+};
+
+use super::client::{BoxFuture, Response, Upstream};
+
+#[derive(Debug, serde::Deserialize)]
+/// A recorded response
+struct Recorded {
+    #[serde(default = "Recorded::default_status")]
+    status: u16,
+
+    #[serde(default)]
+    headers: HashMap<String, String>,
+
+    #[serde(default)]
+    body: serde_json::Value,
+}
+
+impl Recorded {
+    #[inline]
+    const fn default_status() -> u16 {
+        200
+    }
+
+    fn into_response(self) -> Result<Response> {
+        let status = StatusCode::from_u16(self.status)?;
+
+        let mut headers = HeaderMap::with_capacity(self.headers.len() + 1);
+        for (name, value) in self.headers {
+            headers.insert(
+                HeaderName::from_bytes(name.as_bytes())?,
+                HeaderValue::from_str(&value)?,
+            );
+        }
+
+        let body = match self.body {
+            serde_json::Value::Null => Bytes::new(),
+            serde_json::Value::String(body) => Bytes::from(body),
+            body => {
+                headers
+                    .entry(CONTENT_TYPE)
+                    .or_insert(HeaderValue::from_static("application/json"));
+
+                Bytes::from(serde_json::to_vec(&body)?)
+            }
+        };
+
+        Ok(Response {
+            status,
+            headers,
+            body,
+        })
+    }
+}
+
+#[derive(Debug)]
+/// Replays the recorded responses.
+pub(super) struct Fixtures {
+    /// Base URL of the forum, stripped from absolute URLs.
+    base_url: String,
+
+    responses: HashMap<String, Response, foldhash::fast::RandomState>,
+}
+
+impl Fixtures {
+    /// Load the fixture file.
+    pub(super) fn load(base_url: &str, path: &Path) -> Result<Self> {
+        let file = std::fs::read(path)
+            .with_context(|| format!("Read fixtures `{}` error", path.display()))?;
+
+        let responses = serde_json::from_slice::<HashMap<String, Recorded>>(&file)
+            .with_context(|| format!("Parse fixtures `{}` error", path.display()))?
+            .into_iter()
+            .map(|(path, recorded)| {
+                recorded
+                    .into_response()
+                    .with_context(|| format!("Invalid fixture `{path}`"))
+                    .map(|response| (path, response))
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            base_url: base_url.trim_end_matches('/').to_owned(),
+            responses,
+        })
+    }
+}
+
+impl Upstream for Fixtures {
+    fn get<'a>(&'a self, path: &'a str, max_bytes: u64) -> BoxFuture<'a, Result<Response>> {
+        Box::pin(async move {
+            let path = path.strip_prefix(&self.base_url).unwrap_or(path);
+
+            let response = self.responses.get(path).cloned().unwrap_or_else(|| {
+                tracing::debug!("No fixture of `{path}`");
+
+                Response {
+                    status: StatusCode::NOT_FOUND,
+                    headers: HeaderMap::from_iter([(
+                        CONTENT_TYPE,
+                        HeaderValue::from_static("application/json"),
+                    )]),
+                    body: Bytes::from_static(
+                        br#"{"errors":["The requested URL or resource could not be found."],"error_type":"not_found"}"#,
+                    ),
+                }
+            });
+
+            if response.body.len() as u64 > max_bytes {
+                bail!("Response too large")
+            }
+
+            Ok(response)
+        })
+    }
+}
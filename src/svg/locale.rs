@@ -0,0 +1,734 @@
+//! Locales and message catalogs of the cards
+//!
+//! Templates take positional arguments (`{0}`, `{1}`, ...), see [`fill`], so
+//! that each locale can have its own word order.
+
+use std::{fmt, str::FromStr};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// Supported locales
+pub(crate) enum Locale {
+    #[default]
+    /// Simplified Chinese
+    ZhCn,
+
+    /// Traditional Chinese
+    ZhTw,
+
+    /// English
+    En,
+
+    /// Japanese
+    Ja,
+}
+
+impl FromStr for Locale {
+    type Err = ();
+
+    /// Parse from language tag, e.g. `zh-CN`, `zh-Hant`, `en-US` or `ja`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tag = s.trim().to_ascii_lowercase().replace('_', "-");
+
+        let (language, rest) = tag.split_once('-').unwrap_or((&tag, ""));
+
+        match language {
+            "zh" if rest.starts_with("hant")
+                || ["tw", "hk", "mo"].contains(&rest.split('-').next_back().unwrap_or("")) =>
+            {
+                Ok(Self::ZhTw)
+            }
+            "zh" => Ok(Self::ZhCn),
+            "en" => Ok(Self::En),
+            "ja" => Ok(Self::Ja),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Locale {
+    /// From `lang=` query parameter, or the `Accept-Language` header, or the
+    /// default one.
+    pub(crate) fn resolve(lang: Option<&str>, accept_language: Option<&str>) -> Self {
+        lang.and_then(|lang| lang.parse().ok())
+            .or_else(|| accept_language.and_then(Self::from_accept_language))
+            .unwrap_or_default()
+    }
+
+    /// The most preferred supported locale of the `Accept-Language` header.
+    pub(crate) fn from_accept_language(header: &str) -> Option<Self> {
+        let mut preferred: Option<(Self, f32)> = None;
+
+        for item in header.split(',') {
+            let mut parts = item.split(';');
+
+            let Ok(locale) = parts.next().unwrap_or_default().parse::<Self>() else {
+                continue;
+            };
+
+            let q = parts
+                .find_map(|param| param.trim().strip_prefix("q="))
+                .map_or(Some(1.0), |q| q.trim().parse::<f32>().ok())
+                .unwrap_or_default();
+
+            if q > 0.0 && preferred.is_none_or(|(_, preferred_q)| q > preferred_q) {
+                preferred = Some((locale, q));
+            }
+        }
+
+        preferred.map(|(locale, _)| locale)
+    }
+
+    #[inline]
+    /// The message catalog.
+    pub(crate) const fn messages(self) -> &'static Messages {
+        match self {
+            Self::ZhCn => &ZH_CN,
+            Self::ZhTw => &ZH_TW,
+            Self::En => &EN,
+            Self::Ja => &JA,
+        }
+    }
+}
+
+#[derive(Debug)]
+/// Singular and plural forms, the same for languages without plurals.
+pub(crate) struct Plural {
+    pub one: &'static str,
+
+    pub other: &'static str,
+}
+
+impl Plural {
+    #[inline]
+    const fn same(message: &'static str) -> Self {
+        Self {
+            one: message,
+            other: message,
+        }
+    }
+
+    #[inline]
+    /// The form for `n`.
+    pub(crate) const fn of(&self, n: u64) -> &'static str {
+        if n == 1 { self.one } else { self.other }
+    }
+}
+
+#[derive(Debug)]
+/// Message catalog of a locale
+pub(crate) struct Messages {
+    // === General card ===
+    /// `{0}`: visit count
+    pub welcome: &'static str,
+
+    /// Without the visit count
+    pub welcome_anonymous: &'static str,
+
+    /// `{0}`: year, `{1}`: month (see `months`), `{2}`: day, `{3}`: weekday
+    pub today: &'static str,
+
+    /// January ~ December
+    pub months: [&'static str; 12],
+
+    /// Monday ~ Sunday
+    pub weekdays: [&'static str; 7],
+
+    /// `{0}`: day of the year, followed by the countdown.
+    pub day_of_year: &'static str,
+
+    /// `{0}`: target, `{1}`: value, `{2}`: unit
+    pub countdown_left: &'static str,
+
+    /// `{0}`: target
+    pub countdown_today: &'static str,
+
+    /// `{0}`: target, `{1}`: value, `{2}`: unit
+    pub countdown_past: &'static str,
+
+    pub new_year: &'static str,
+
+    pub lunar_new_year: &'static str,
+
+    pub day: Plural,
+
+    pub hour: Plural,
+
+    pub minute: Plural,
+
+    /// `{0}`: festival or solar term
+    pub festival_today: &'static str,
+
+    /// `{0}`: festival or solar term, `{1}`: days left, `{2}`: unit
+    pub festival_left: &'static str,
+
+    /// Traditional festivals, in the order of `festival::FESTIVALS`, then New
+    /// Year's Eve.
+    pub festivals: [&'static str; 11],
+
+    /// Solar terms, from 小寒.
+    pub solar_terms: [&'static str; 24],
+
+    /// Morning, noon, afternoon, evening and late night, see
+    /// `time_of_day::Period`.
+    pub greetings: [&'static str; 5],
+
+    // === Linux.do cards ===
+    /// Bio of users without one
+    pub default_bio: &'static str,
+
+    pub joined: &'static str,
+
+    pub last_seen: &'static str,
+
+    /// Metrics, in the order of `Metric::ALL`.
+    pub metrics: [&'static str; 8],
+
+    /// `{0}`: visit count
+    pub views: Plural,
+
+    pub badges: &'static str,
+
+    /// No flair or badges
+    pub no_badges: &'static str,
+
+    /// Not found, unavailable, not allowed, quota exhausted and unclaimed.
+    pub status: [&'static str; 5],
+
+    /// Data is being fetched from upstream.
+    pub fetching: &'static str,
+
+    /// `{0}`: when the data is fetched, or why there's no data (see
+    /// `footer_status`)
+    pub updated: &'static str,
+
+    /// Not found, unavailable and not allowed, short ones for the footer.
+    pub footer_status: [&'static str; 3],
+
+    /// `{0}`: minutes, hours, days, weeks, months and years ago.
+    pub ago: [Plural; 6],
+
+    /// `{0}`: minutes and hours, with decimals.
+    pub duration: [&'static str; 2],
+
+    /// `{0}`: metric
+    pub leaderboard_title: &'static str,
+
+    pub no_data: &'static str,
+
+    /// `{0}`: users still being fetched
+    pub pending: Plural,
+
+    pub compare_title: &'static str,
+
+    pub heatmap_less: &'static str,
+
+    pub heatmap_more: &'static str,
+
+    /// `{0}`: weeks, `{1}`: posts and replies
+    pub heatmap_summary: &'static str,
+}
+
+/// Fill the positional arguments (`{0}`, `{1}`, ...) of the template.
+pub(crate) fn fill(template: &str, args: &[&dyn fmt::Display]) -> String {
+    use std::fmt::Write;
+
+    let mut filled = String::with_capacity(template.len() + 16);
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        filled.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let arg = rest
+            .find('}')
+            .and_then(|end| Some((rest[1..end].parse::<usize>().ok()?, end)))
+            .and_then(|(idx, end)| Some((args.get(idx)?, end)));
+
+        match arg {
+            Some((arg, end)) => {
+                let _ = write!(filled, "{arg}");
+                rest = &rest[end + 1..];
+            }
+            None => {
+                filled.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    filled.push_str(rest);
+    filled
+}
+
+static ZH_CN: Messages = Messages {
+    welcome: "欢迎您，第 {0} 位访问本页面的朋友 🎉",
+    welcome_anonymous: "欢迎您，朋友 🎉",
+    today: "今天是新历 {0} 年 {1} 月 {2} 日，星期{3}",
+    months: [
+        "1", "2", "3", "4", "5", "6", "7", "8", "9", "10", "11", "12",
+    ],
+    weekdays: ["一", "二", "三", "四", "五", "六", "日"],
+    day_of_year: "已经是今年的第 {0} 天啦，",
+    countdown_left: "离{0}还有 {1} {2}",
+    countdown_today: "今天就是{0}啦",
+    countdown_past: "{0}已经过去 {1} {2}",
+    new_year: "新历新年",
+    lunar_new_year: "农历新年",
+    day: Plural::same("天"),
+    hour: Plural::same("小时"),
+    minute: Plural::same("分钟"),
+    festival_today: "今天是{0}",
+    festival_left: "距离{0}还有 {1} {2}",
+    festivals: [
+        "春节",
+        "元宵节",
+        "龙抬头",
+        "端午节",
+        "七夕节",
+        "中元节",
+        "中秋节",
+        "重阳节",
+        "腊八节",
+        "小年",
+        "除夕",
+    ],
+    solar_terms: [
+        "小寒", "大寒", "立春", "雨水", "惊蛰", "春分", "清明", "谷雨", "立夏", "小满", "芒种",
+        "夏至", "小暑", "大暑", "立秋", "处暑", "白露", "秋分", "寒露", "霜降", "立冬", "小雪",
+        "大雪", "冬至",
+    ],
+    greetings: [
+        "早上好 ☀️",
+        "中午好，记得吃午饭 🍚",
+        "下午好 ☕",
+        "晚上好 🌙",
+        "夜深了，早点休息哦 🌃",
+    ],
+    default_bio: "小白一枚",
+    joined: "🕒注册时间",
+    last_seen: "🕗最近上线",
+    metrics: [
+        "🛎️访问天数",
+        "⌛阅读时间",
+        "📰浏览话题",
+        "📑已读帖子",
+        "💝已送出赞",
+        "👍已收到赞",
+        "📖创建帖子",
+        "💡解决方案",
+    ],
+    views: Plural::same("{0} 次浏览"),
+    badges: "🏅徽章",
+    no_badges: "暂无",
+    status: [
+        "⚠️用户不存在, 请检查用户名",
+        "⚠️上游暂不可用, 请稍后再试",
+        "⚠️该用户不在允许列表中",
+        "⚠️本小时获取额度已用完, 请稍后再试",
+        "⚠️计数器未认领, 无法获取该用户数据",
+    ],
+    fetching: "... [正在获取数据]",
+    updated: "更新于: {0}",
+    footer_status: ["... [用户不存在]", "... [上游不可用]", "... [不允许获取]"],
+    ago: [
+        Plural::same("{0} 分钟前"),
+        Plural::same("{0} 个小时前"),
+        Plural::same("{0} 天前"),
+        Plural::same("{0} 周前"),
+        Plural::same("{0} 个月前"),
+        Plural::same("{0} 年前"),
+    ],
+    duration: ["{0} 分钟", "{0} 小时"],
+    leaderboard_title: "🏆 {0} 排行榜",
+    no_data: "暂无数据",
+    pending: Plural::same("另有 {0} 位用户数据获取中"),
+    compare_title: "⚔️数据对比",
+    heatmap_less: "少",
+    heatmap_more: "多",
+    heatmap_summary: "最近 {0} 周发帖/回复 {1} 次",
+};
+
+static ZH_TW: Messages = Messages {
+    welcome: "歡迎您，第 {0} 位造訪本頁面的朋友 🎉",
+    welcome_anonymous: "歡迎您，朋友 🎉",
+    today: "今天是國曆 {0} 年 {1} 月 {2} 日，星期{3}",
+    months: [
+        "1", "2", "3", "4", "5", "6", "7", "8", "9", "10", "11", "12",
+    ],
+    weekdays: ["一", "二", "三", "四", "五", "六", "日"],
+    day_of_year: "已經是今年的第 {0} 天啦，",
+    countdown_left: "離{0}還有 {1} {2}",
+    countdown_today: "今天就是{0}啦",
+    countdown_past: "{0}已經過去 {1} {2}",
+    new_year: "國曆新年",
+    lunar_new_year: "農曆新年",
+    day: Plural::same("天"),
+    hour: Plural::same("小時"),
+    minute: Plural::same("分鐘"),
+    festival_today: "今天是{0}",
+    festival_left: "距離{0}還有 {1} {2}",
+    festivals: [
+        "春節",
+        "元宵節",
+        "龍抬頭",
+        "端午節",
+        "七夕節",
+        "中元節",
+        "中秋節",
+        "重陽節",
+        "臘八節",
+        "小年",
+        "除夕",
+    ],
+    solar_terms: [
+        "小寒", "大寒", "立春", "雨水", "驚蟄", "春分", "清明", "穀雨", "立夏", "小滿", "芒種",
+        "夏至", "小暑", "大暑", "立秋", "處暑", "白露", "秋分", "寒露", "霜降", "立冬", "小雪",
+        "大雪", "冬至",
+    ],
+    greetings: [
+        "早安 ☀️",
+        "午安，記得吃午餐 🍚",
+        "午安 ☕",
+        "晚上好 🌙",
+        "夜深了，早點休息喔 🌃",
+    ],
+    default_bio: "小白一枚",
+    joined: "🕒註冊時間",
+    last_seen: "🕗最近上線",
+    metrics: [
+        "🛎️造訪天數",
+        "⌛閱讀時間",
+        "📰瀏覽話題",
+        "📑已讀貼文",
+        "💝已送出讚",
+        "👍已收到讚",
+        "📖建立貼文",
+        "💡解決方案",
+    ],
+    views: Plural::same("{0} 次瀏覽"),
+    badges: "🏅徽章",
+    no_badges: "暫無",
+    status: [
+        "⚠️使用者不存在, 請檢查使用者名稱",
+        "⚠️上游暫時無法使用, 請稍後再試",
+        "⚠️該使用者不在允許清單中",
+        "⚠️本小時取得額度已用完, 請稍後再試",
+        "⚠️計數器未認領, 無法取得該使用者資料",
+    ],
+    fetching: "... [正在取得資料]",
+    updated: "更新於: {0}",
+    footer_status: [
+        "... [使用者不存在]",
+        "... [上游無法使用]",
+        "... [不允許取得]",
+    ],
+    ago: [
+        Plural::same("{0} 分鐘前"),
+        Plural::same("{0} 個小時前"),
+        Plural::same("{0} 天前"),
+        Plural::same("{0} 週前"),
+        Plural::same("{0} 個月前"),
+        Plural::same("{0} 年前"),
+    ],
+    duration: ["{0} 分鐘", "{0} 小時"],
+    leaderboard_title: "🏆 {0} 排行榜",
+    no_data: "暫無資料",
+    pending: Plural::same("另有 {0} 位使用者資料取得中"),
+    compare_title: "⚔️資料對比",
+    heatmap_less: "少",
+    heatmap_more: "多",
+    heatmap_summary: "最近 {0} 週發文/回覆 {1} 次",
+};
+
+static EN: Messages = Messages {
+    welcome: "Welcome, visitor No. {0} of this page 🎉",
+    welcome_anonymous: "Welcome, my friend 🎉",
+    today: "Today is {3}, {1} {2}, {0}",
+    months: [
+        "January",
+        "February",
+        "March",
+        "April",
+        "May",
+        "June",
+        "July",
+        "August",
+        "September",
+        "October",
+        "November",
+        "December",
+    ],
+    weekdays: [
+        "Monday",
+        "Tuesday",
+        "Wednesday",
+        "Thursday",
+        "Friday",
+        "Saturday",
+        "Sunday",
+    ],
+    day_of_year: "Day {0} of the year, ",
+    countdown_left: "{1} {2} until {0}",
+    countdown_today: "today is {0}",
+    countdown_past: "{0} was {1} {2} ago",
+    new_year: "New Year",
+    lunar_new_year: "Lunar New Year",
+    day: Plural {
+        one: "day",
+        other: "days",
+    },
+    hour: Plural {
+        one: "hour",
+        other: "hours",
+    },
+    minute: Plural {
+        one: "minute",
+        other: "minutes",
+    },
+    festival_today: "Today is {0}",
+    festival_left: "{1} {2} until {0}",
+    festivals: [
+        "Spring Festival",
+        "Lantern Festival",
+        "Dragon Head Raising Day",
+        "Dragon Boat Festival",
+        "Qixi Festival",
+        "Ghost Festival",
+        "Mid-Autumn Festival",
+        "Double Ninth Festival",
+        "Laba Festival",
+        "Little New Year",
+        "New Year's Eve",
+    ],
+    solar_terms: [
+        "Minor Cold",
+        "Major Cold",
+        "Start of Spring",
+        "Rain Water",
+        "Awakening of Insects",
+        "Spring Equinox",
+        "Pure Brightness",
+        "Grain Rain",
+        "Start of Summer",
+        "Grain Buds",
+        "Grain in Ear",
+        "Summer Solstice",
+        "Minor Heat",
+        "Major Heat",
+        "Start of Autumn",
+        "End of Heat",
+        "White Dew",
+        "Autumn Equinox",
+        "Cold Dew",
+        "Frost's Descent",
+        "Start of Winter",
+        "Minor Snow",
+        "Major Snow",
+        "Winter Solstice",
+    ],
+    greetings: [
+        "Good morning ☀️",
+        "Good noon, enjoy your lunch 🍚",
+        "Good afternoon ☕",
+        "Good evening 🌙",
+        "It's late, get some rest 🌃",
+    ],
+    default_bio: "Just a newbie",
+    joined: "🕒Joined",
+    last_seen: "🕗Last seen",
+    metrics: [
+        "🛎️Days visited",
+        "⌛Read time",
+        "📰Topics",
+        "📑Posts read",
+        "💝Likes given",
+        "👍Likes",
+        "📖Posts",
+        "💡Solutions",
+    ],
+    views: Plural {
+        one: "{0} view",
+        other: "{0} views",
+    },
+    badges: "🏅Badges",
+    no_badges: "None",
+    status: [
+        "⚠️User not found, please check the username",
+        "⚠️Upstream unavailable, please try again later",
+        "⚠️The user is not in the allow list",
+        "⚠️Fetch quota of this hour used up, please try again later",
+        "⚠️Counter not claimed, the user's data is unavailable",
+    ],
+    fetching: "... [FETCHING UPSTREAM]",
+    updated: "Updated: {0}",
+    footer_status: [
+        "... [USER NOT FOUND]",
+        "... [UPSTREAM UNAVAILABLE]",
+        "... [FETCH NOT ALLOWED]",
+    ],
+    ago: [
+        Plural {
+            one: "{0} minute ago",
+            other: "{0} minutes ago",
+        },
+        Plural {
+            one: "{0} hour ago",
+            other: "{0} hours ago",
+        },
+        Plural {
+            one: "{0} day ago",
+            other: "{0} days ago",
+        },
+        Plural {
+            one: "{0} week ago",
+            other: "{0} weeks ago",
+        },
+        Plural {
+            one: "{0} month ago",
+            other: "{0} months ago",
+        },
+        Plural {
+            one: "{0} year ago",
+            other: "{0} years ago",
+        },
+    ],
+    duration: ["{0} minutes", "{0} hours"],
+    leaderboard_title: "🏆 {0} Leaderboard",
+    no_data: "No data yet",
+    pending: Plural {
+        one: "{0} more user being fetched",
+        other: "{0} more users being fetched",
+    },
+    compare_title: "⚔️Comparison",
+    heatmap_less: "Less",
+    heatmap_more: "More",
+    heatmap_summary: "Posts and replies in the last {0} weeks: {1}",
+};
+
+static JA: Messages = Messages {
+    welcome: "ようこそ、このページの {0} 人目の訪問者さん 🎉",
+    welcome_anonymous: "ようこそ 🎉",
+    today: "今日は {0} 年 {1} 月 {2} 日（{3}曜日）",
+    months: [
+        "1", "2", "3", "4", "5", "6", "7", "8", "9", "10", "11", "12",
+    ],
+    weekdays: ["月", "火", "水", "木", "金", "土", "日"],
+    day_of_year: "今年の {0} 日目、",
+    countdown_left: "{0}まであと {1} {2}",
+    countdown_today: "今日は{0}です",
+    countdown_past: "{0}から {1} {2}が経ちました",
+    new_year: "新年",
+    lunar_new_year: "旧正月",
+    day: Plural::same("日"),
+    hour: Plural::same("時間"),
+    minute: Plural::same("分"),
+    festival_today: "今日は{0}です",
+    festival_left: "{0}まであと {1} {2}",
+    festivals: [
+        "春節",
+        "元宵節",
+        "龍抬頭",
+        "端午節",
+        "七夕",
+        "中元節",
+        "中秋節",
+        "重陽節",
+        "臘八節",
+        "小年",
+        "除夕",
+    ],
+    solar_terms: [
+        "小寒", "大寒", "立春", "雨水", "啓蟄", "春分", "清明", "穀雨", "立夏", "小満", "芒種",
+        "夏至", "小暑", "大暑", "立秋", "処暑", "白露", "秋分", "寒露", "霜降", "立冬", "小雪",
+        "大雪", "冬至",
+    ],
+    greetings: [
+        "おはようございます ☀️",
+        "こんにちは、お昼ごはんの時間です 🍚",
+        "こんにちは ☕",
+        "こんばんは 🌙",
+        "夜も遅いので、早めに休みましょう 🌃",
+    ],
+    default_bio: "新人です",
+    joined: "🕒登録",
+    last_seen: "🕗最終ログイン",
+    metrics: [
+        "🛎️訪問日数",
+        "⌛閲覧時間",
+        "📰閲覧トピック",
+        "📑既読投稿",
+        "💝送ったいいね",
+        "👍いいね",
+        "📖投稿数",
+        "💡解決策",
+    ],
+    views: Plural::same("{0} 回閲覧"),
+    badges: "🏅バッジ",
+    no_badges: "なし",
+    status: [
+        "⚠️ユーザーが存在しません、ユーザー名を確認してください",
+        "⚠️上流が利用できません、後でもう一度お試しください",
+        "⚠️このユーザーは許可リストにありません",
+        "⚠️今時間の取得枠を使い切りました、後でもう一度お試しください",
+        "⚠️カウンターが未登録のため、このユーザーのデータを取得できません",
+    ],
+    fetching: "... [データ取得中]",
+    updated: "更新: {0}",
+    footer_status: [
+        "... [ユーザーが存在しません]",
+        "... [上流が利用できません]",
+        "... [取得が許可されていません]",
+    ],
+    ago: [
+        Plural::same("{0} 分前"),
+        Plural::same("{0} 時間前"),
+        Plural::same("{0} 日前"),
+        Plural::same("{0} 週間前"),
+        Plural::same("{0} か月前"),
+        Plural::same("{0} 年前"),
+    ],
+    duration: ["{0} 分", "{0} 時間"],
+    leaderboard_title: "🏆 {0} ランキング",
+    no_data: "データなし",
+    pending: Plural::same("ほか {0} 人のデータを取得中"),
+    compare_title: "⚔️データ比較",
+    heatmap_less: "少",
+    heatmap_more: "多",
+    heatmap_summary: "最近 {0} 週間の投稿・返信 {1} 回",
+};
+
+#[test]
+fn test_locale() {
+    assert_eq!("zh-CN".parse(), Ok(Locale::ZhCn));
+    assert_eq!("zh_hans_SG".parse(), Ok(Locale::ZhCn));
+    assert_eq!("zh-TW".parse(), Ok(Locale::ZhTw));
+    assert_eq!("zh-Hant".parse(), Ok(Locale::ZhTw));
+    assert_eq!("zh-Hant-HK".parse(), Ok(Locale::ZhTw));
+    assert_eq!("EN-us".parse(), Ok(Locale::En));
+    assert_eq!("ja".parse(), Ok(Locale::Ja));
+    assert_eq!("fr-FR".parse::<Locale>(), Err(()));
+
+    assert_eq!(
+        Locale::from_accept_language("fr-FR,fr;q=0.9,en;q=0.8,ja;q=0.9"),
+        Some(Locale::Ja)
+    );
+    assert_eq!(
+        Locale::from_accept_language("zh-TW;q=0, en-GB"),
+        Some(Locale::En)
+    );
+    assert_eq!(Locale::from_accept_language("*, fr"), None);
+
+    assert_eq!(Locale::resolve(Some("ja"), Some("en")), Locale::Ja);
+    assert_eq!(Locale::resolve(Some("xx"), Some("en")), Locale::En);
+    assert_eq!(Locale::resolve(None, None), Locale::ZhCn);
+
+    assert_eq!(
+        fill("{1} {2} until {0}", &[&"New Year", &1, &"day"]),
+        "1 day until New Year"
+    );
+    assert_eq!(fill("{0} {x} {5} {", &[&"a"]), "a {x} {5} {");
+
+    assert_eq!(EN.ago[0].of(1), "{0} minute ago");
+    assert_eq!(EN.ago[0].of(0), "{0} minutes ago");
+    assert_eq!(ZH_CN.ago[0].of(1), ZH_CN.ago[0].of(2));
+}
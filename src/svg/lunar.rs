@@ -0,0 +1,190 @@
+//! Chinese lunisolar calendar (农历), table driven, 1900 ~ 2100
+
+use chrono::{Datelike, Days, NaiveDate};
+
+/// The first year covered by [`LUNAR_INFO`]
+const FIRST_YEAR: i32 = 1900;
+
+/// The last year covered by [`LUNAR_INFO`]
+const LAST_YEAR: i32 = 2100;
+
+/// Lunar New Year of [`FIRST_YEAR`], 1900-01-31
+const FIRST_NEW_YEAR: NaiveDate = NaiveDate::from_ymd_opt(1900, 1, 31).unwrap();
+
+#[rustfmt::skip]
+/// Months of each lunar year since [`FIRST_YEAR`]
+///
+/// - bit 0 ~ 3: the leap month, 0 if none.
+/// - bit 4 ~ 15: whether month 12 ~ 1 (from low to high) has 30 days, or 29.
+/// - bit 16: whether the leap month has 30 days, or 29.
+static LUNAR_INFO: [u32; (LAST_YEAR - FIRST_YEAR + 1) as usize] = [
+    // 1900 ~ 1909
+    0x04bd8, 0x04ae0, 0x0a570, 0x054d5, 0x0d260, 0x0d950, 0x16554, 0x056a0, 0x09ad0, 0x055d2,
+    // 1910 ~ 1919
+    0x04ae0, 0x0a5b6, 0x0a4d0, 0x0d250, 0x1d255, 0x0b540, 0x0d6a0, 0x0ada2, 0x095b0, 0x14977,
+    // 1920 ~ 1929
+    0x04970, 0x0a4b0, 0x0b4b5, 0x06a50, 0x06d40, 0x1ab54, 0x02b60, 0x09570, 0x052f2, 0x04970,
+    // 1930 ~ 1939
+    0x06566, 0x0d4a0, 0x0ea50, 0x16a95, 0x05ad0, 0x02b60, 0x186e3, 0x092e0, 0x1c8d7, 0x0c950,
+    // 1940 ~ 1949
+    0x0d4a0, 0x1d8a6, 0x0b550, 0x056a0, 0x1a5b4, 0x025d0, 0x092d0, 0x0d2b2, 0x0a950, 0x0b557,
+    // 1950 ~ 1959
+    0x06ca0, 0x0b550, 0x15355, 0x04da0, 0x0a5b0, 0x14573, 0x052b0, 0x0a9a8, 0x0e950, 0x06aa0,
+    // 1960 ~ 1969
+    0x0aea6, 0x0ab50, 0x04b60, 0x0aae4, 0x0a570, 0x05260, 0x0f263, 0x0d950, 0x05b57, 0x056a0,
+    // 1970 ~ 1979
+    0x096d0, 0x04dd5, 0x04ad0, 0x0a4d0, 0x0d4d4, 0x0d250, 0x0d558, 0x0b540, 0x0b6a0, 0x195a6,
+    // 1980 ~ 1989
+    0x095b0, 0x049b0, 0x0a974, 0x0a4b0, 0x0b27a, 0x06a50, 0x06d40, 0x0af46, 0x0ab60, 0x09570,
+    // 1990 ~ 1999
+    0x04af5, 0x04970, 0x064b0, 0x074a3, 0x0ea50, 0x06b58, 0x05ac0, 0x0ab60, 0x096d5, 0x092e0,
+    // 2000 ~ 2009
+    0x0c960, 0x0d954, 0x0d4a0, 0x0da50, 0x07552, 0x056a0, 0x0abb7, 0x025d0, 0x092d0, 0x0cab5,
+    // 2010 ~ 2019
+    0x0a950, 0x0b4a0, 0x0baa4, 0x0ad50, 0x055d9, 0x04ba0, 0x0a5b0, 0x15176, 0x052b0, 0x0a930,
+    // 2020 ~ 2029
+    0x07954, 0x06aa0, 0x0ad50, 0x05b52, 0x04b60, 0x0a6e6, 0x0a4e0, 0x0d260, 0x0ea65, 0x0d530,
+    // 2030 ~ 2039
+    0x05aa0, 0x076a3, 0x096d0, 0x04afb, 0x04ad0, 0x0a4d0, 0x1d0b6, 0x0d250, 0x0d520, 0x0dd45,
+    // 2040 ~ 2049
+    0x0b5a0, 0x056d0, 0x055b2, 0x049b0, 0x0a577, 0x0a4b0, 0x0aa50, 0x1b255, 0x06d20, 0x0ada0,
+    // 2050 ~ 2059
+    0x14b63, 0x09370, 0x049f8, 0x04970, 0x064b0, 0x168a6, 0x0ea50, 0x06b20, 0x1a6c4, 0x0aae0,
+    // 2060 ~ 2069
+    0x092e0, 0x0d2e3, 0x0c960, 0x0d557, 0x0d4a0, 0x0da50, 0x05d55, 0x056a0, 0x0a6d0, 0x055d4,
+    // 2070 ~ 2079
+    0x052d0, 0x0a9b8, 0x0a950, 0x0b4a0, 0x0b6a6, 0x0ad50, 0x055a0, 0x0aba4, 0x0a5b0, 0x052b0,
+    // 2080 ~ 2089
+    0x0b273, 0x06930, 0x07337, 0x06aa0, 0x0ad50, 0x14b55, 0x04b60, 0x0a570, 0x054e4, 0x0d160,
+    // 2090 ~ 2099
+    0x0e968, 0x0d520, 0x0daa0, 0x16aa6, 0x056d0, 0x04ae0, 0x0a9d4, 0x0a2d0, 0x0d150, 0x0f252,
+    // 2100 ~ 2100
+    0x0d520,];
+
+#[inline]
+/// Index of the given year in [`LUNAR_INFO`], `None` if out of range.
+fn index(year: i32) -> Option<usize> {
+    (FIRST_YEAR..=LAST_YEAR)
+        .contains(&year)
+        .then(|| (year - FIRST_YEAR) as usize)
+}
+
+#[inline]
+/// Days of the given month (1 ~ 12), not the leap one.
+const fn month_days(info: u32, month: u8) -> u64 {
+    if info & (0x10000 >> month) != 0 {
+        30
+    } else {
+        29
+    }
+}
+
+#[inline]
+/// Days of the leap month, 0 if none.
+const fn leap_month_days(info: u32) -> u64 {
+    match info & 0xf {
+        0 => 0,
+        _ if info & 0x10000 != 0 => 30,
+        _ => 29,
+    }
+}
+
+#[inline]
+/// Days of the given lunar year.
+fn year_days(info: u32) -> u64 {
+    (1..=12).map(|month| month_days(info, month)).sum::<u64>() + leap_month_days(info)
+}
+
+/// Lunar New Year (春节) of the given year, `None` if out of range.
+pub(crate) fn new_year(year: i32) -> Option<NaiveDate> {
+    let days = LUNAR_INFO[..index(year)?]
+        .iter()
+        .copied()
+        .map(year_days)
+        .sum();
+
+    FIRST_NEW_YEAR.checked_add_days(Days::new(days))
+}
+
+/// The next Lunar New Year since `today` (inclusive), `None` if out of range.
+pub(crate) fn next_new_year(today: NaiveDate) -> Option<NaiveDate> {
+    match new_year(today.year()) {
+        Some(new_year) if new_year >= today => Some(new_year),
+        _ => new_year(today.year() + 1),
+    }
+}
+
+/// New Year's Eve (除夕), the last day of the given lunar year.
+pub(crate) fn new_years_eve(year: i32) -> Option<NaiveDate> {
+    new_year(year)?.checked_add_days(Days::new(year_days(LUNAR_INFO[index(year)?]) - 1))
+}
+
+/// The solar date of the given lunar date, not in the leap month. `None` if out
+/// of range or there's no such day (e.g. the 30th of a short month).
+pub(crate) fn to_solar(year: i32, month: u8, day: u8) -> Option<NaiveDate> {
+    let info = LUNAR_INFO[index(year)?];
+
+    if !(1..=12).contains(&month) || day == 0 || u64::from(day) > month_days(info, month) {
+        return None;
+    }
+
+    // The leap month follows the month of the same number.
+    let leap_month = (info & 0xf) as u8;
+    let days = (1..month).map(|month| month_days(info, month)).sum::<u64>()
+        + if leap_month != 0 && leap_month < month {
+            leap_month_days(info)
+        } else {
+            0
+        }
+        + u64::from(day - 1);
+
+    new_year(year)?.checked_add_days(Days::new(days))
+}
+
+#[test]
+fn test_new_year() {
+    let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+
+    for (year, expected) in [
+        (1900, date(1900, 1, 31)),
+        (1949, date(1949, 1, 29)),
+        (1984, date(1984, 2, 2)),
+        (2000, date(2000, 2, 5)),
+        (2024, date(2024, 2, 10)),
+        (2025, date(2025, 1, 29)),
+        (2026, date(2026, 2, 17)),
+        (2033, date(2033, 1, 31)),
+        (2050, date(2050, 1, 23)),
+        (2100, date(2100, 2, 9)),
+    ] {
+        assert_eq!(new_year(year), Some(expected), "{year}");
+    }
+    assert_eq!(new_year(1899), None);
+    assert_eq!(new_year(2101), None);
+
+    assert_eq!(next_new_year(date(2025, 3, 1)), Some(date(2026, 2, 17)));
+    assert_eq!(next_new_year(date(2026, 2, 16)), Some(date(2026, 2, 17)));
+    assert_eq!(next_new_year(date(2026, 2, 17)), Some(date(2026, 2, 17)));
+    assert_eq!(next_new_year(date(2026, 2, 18)), Some(date(2027, 2, 6)));
+    assert_eq!(next_new_year(date(2100, 3, 1)), None);
+}
+
+#[test]
+fn test_to_solar() {
+    let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+
+    assert_eq!(to_solar(2024, 1, 1), Some(date(2024, 2, 10)));
+    // After the leap months, 2020 (4), 2023 (2) and 2025 (6)
+    assert_eq!(to_solar(2020, 8, 15), Some(date(2020, 10, 1)));
+    assert_eq!(to_solar(2023, 5, 5), Some(date(2023, 6, 22)));
+    assert_eq!(to_solar(2025, 8, 15), Some(date(2025, 10, 6)));
+    // Before the leap month
+    assert_eq!(to_solar(2025, 5, 5), Some(date(2025, 5, 31)));
+
+    assert_eq!(to_solar(2025, 13, 1), None);
+    assert_eq!(to_solar(2025, 1, 0), None);
+    assert_eq!(to_solar(2101, 1, 1), None);
+
+    assert_eq!(new_years_eve(2025), Some(date(2026, 2, 16)));
+    assert_eq!(new_years_eve(2100), Some(date(2101, 1, 28)));
+}
@@ -6,7 +6,10 @@ use macro_toolset::{
 };
 use rand::Rng;
 
-use crate::utils::{self, Queries};
+use crate::{
+    config::Defaults,
+    utils::{self, Queries},
+};
 
 include!(concat!(env!("OUT_DIR"), "/moe-counter.rs"));
 
@@ -54,17 +57,24 @@ impl Default for MoeCounterImpl<'_> {
     }
 }
 
+#[inline]
+/// Whether the given theme name is known (`random` included).
+pub(crate) fn is_valid_theme(theme: &str) -> bool {
+    theme == "random" || moe_counter_list::THEMES_LIST.contains(&theme)
+}
+
 impl<'i> MoeCounterImpl<'i> {
-    pub(crate) fn from_queries(queries: &'i Queries) -> Self {
+    /// Create from queries, missing ones fallback to given [`Defaults`].
+    pub(crate) fn from_queries(queries: &'i Queries, defaults: &'i Defaults) -> Self {
         Self {
             theme: queries
                 .get("theme")
                 .map(AsRef::as_ref)
-                .unwrap_or("moebooru"),
+                .unwrap_or(&defaults.theme),
             padding: queries
                 .get("padding")
                 .and_then(|padding| padding.parse().ok())
-                .unwrap_or(7),
+                .unwrap_or(defaults.padding),
             offset: queries
                 .get("offset")
                 .and_then(|offset| offset.parse().ok())
@@ -73,14 +83,15 @@ impl<'i> MoeCounterImpl<'i> {
             scale: queries
                 .get("scale")
                 .and_then(|scale| scale.parse().ok())
-                .unwrap_or(1.0),
+                .unwrap_or(defaults.scale),
             pixelated: queries
                 .get("pixelated")
                 .map(|pixelated| pixelated == "1" || pixelated == "true")
                 .unwrap_or(false),
             darkmode: queries
                 .get("darkmode")
-                .map(|darkmode| darkmode == "1" || darkmode == "true"),
+                .map(|darkmode| darkmode == "1" || darkmode == "true")
+                .or(defaults.darkmode),
             prefix: queries.get("prefix").and_then(|prefix| prefix.parse().ok()),
         }
     }
@@ -0,0 +1,97 @@
+//! The 24 solar terms (二十四节气)
+//!
+//! A solar term begins when the apparent longitude of the sun reaches a
+//! multiple of 15°, which is computed with the low accuracy formulas of Jean
+//! Meeus' *Astronomical Algorithms* (about 0.01°, i.e. within a quarter of an
+//! hour). The date is in China Standard Time, as the Chinese calendar does.
+
+use chrono::NaiveDate;
+
+/// Solar terms of a year, from 小寒 (285°), see
+/// [`Messages::solar_terms`](super::locale::Messages::solar_terms) for the
+/// names.
+pub(crate) const COUNT: usize = 24;
+
+/// Julian day of J2000.0, 2000-01-01 12:00
+const J2000: f64 = 2_451_545.0;
+
+/// Days of a tropical year
+const TROPICAL_YEAR: f64 = 365.242_2;
+
+/// Days since CE (0001-01-01 as day 1) of 2000-01-01
+const J2000_DAYS_FROM_CE: i32 = 730_120;
+
+/// China Standard Time, UTC+8, in days
+const CST_OFFSET: f64 = 8.0 / 24.0;
+
+/// The apparent longitude of the sun in degrees at the given Julian day.
+fn apparent_longitude(jd: f64) -> f64 {
+    let t = (jd - J2000) / 36525.0;
+
+    // Geometric mean longitude and mean anomaly
+    let l0 = 280.466_46 + 36_000.769_83 * t + 0.000_303_2 * t * t;
+    let m = (357.529_11 + 35_999.050_29 * t - 0.000_153_7 * t * t).to_radians();
+
+    // Equation of the center
+    let c = (1.914_602 - 0.004_817 * t - 0.000_014 * t * t) * m.sin()
+        + (0.019_993 - 0.000_101 * t) * (2.0 * m).sin()
+        + 0.000_289 * (3.0 * m).sin();
+
+    // Nutation and aberration
+    let omega = (125.04 - 1934.136 * t).to_radians();
+
+    (l0 + c - 0.005_69 - 0.004_78 * omega.sin()).rem_euclid(360.0)
+}
+
+/// The date of the given solar term (0 ~ 23, see [`COUNT`]) in the given year.
+///
+/// ΔT (about a minute nowadays) is ignored, which is negligible at this
+/// accuracy.
+pub(crate) fn date(year: i32, term: usize) -> Option<NaiveDate> {
+    let longitude = (285.0 + 15.0 * term as f64).rem_euclid(360.0);
+
+    // Start from the mean date, 小寒 is around January 5th.
+    let mut jd = J2000 + f64::from(year - 2000) * TROPICAL_YEAR + 4.0 + term as f64 * 15.22;
+    for _ in 0..10 {
+        let delta = (longitude - apparent_longitude(jd) + 180.0).rem_euclid(360.0) - 180.0;
+        jd += delta * TROPICAL_YEAR / 360.0;
+
+        if delta.abs() < 1e-7 {
+            break;
+        }
+    }
+
+    // Julian days start from noon.
+    let days = (jd - J2000 + 0.5 + CST_OFFSET).floor() as i32;
+
+    NaiveDate::from_num_days_from_ce_opt(J2000_DAYS_FROM_CE + days)
+}
+
+#[test]
+fn test_date() {
+    let date_of = |name| {
+        let term = super::locale::Locale::ZhCn
+            .messages()
+            .solar_terms
+            .iter()
+            .position(|n| *n == name)
+            .unwrap();
+        move |year| date(year, term).unwrap().format("%Y-%m-%d").to_string()
+    };
+
+    assert_eq!(date_of("小寒")(2025), "2025-01-05");
+    assert_eq!(date_of("立春")(2025), "2025-02-03");
+    assert_eq!(date_of("立春")(2026), "2026-02-04");
+    assert_eq!(date_of("春分")(2000), "2000-03-20");
+    assert_eq!(date_of("清明")(2025), "2025-04-04");
+    assert_eq!(date_of("清明")(2026), "2026-04-05");
+    assert_eq!(date_of("谷雨")(2025), "2025-04-20");
+    assert_eq!(date_of("夏至")(2024), "2024-06-21");
+    assert_eq!(date_of("处暑")(2025), "2025-08-23");
+    assert_eq!(date_of("寒露")(2024), "2024-10-08");
+    assert_eq!(date_of("大雪")(2024), "2024-12-06");
+    assert_eq!(date_of("大雪")(2025), "2025-12-07");
+    assert_eq!(date_of("冬至")(2023), "2023-12-22");
+    assert_eq!(date_of("冬至")(2025), "2025-12-21");
+    assert_eq!(date_of("冬至")(2026), "2026-12-22");
+}
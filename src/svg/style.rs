@@ -0,0 +1,279 @@
+//! Colors and typography of the general card
+//!
+//! All values come from query parameters, validated against allow-lists (hex
+//! or named colors, known font families, bounded sizes) so that nothing can be
+//! injected into the CSS.
+
+use std::borrow::Cow;
+
+use macro_toolset::{str_concat, string::StringExtT};
+
+use super::color::{ColorScheme, DARK, LIGHT, parse_hex_color};
+use crate::utils::Queries;
+
+/// Default border radius of the background, px
+const DEFAULT_RADIUS: u8 = 6;
+
+/// Max border radius of the background, px
+const MAX_RADIUS: u8 = 32;
+
+/// Font size range of the text, px
+const FONT_SIZE_RANGE: std::ops::RangeInclusive<u8> = 8..=24;
+
+/// Max colors of the background gradient
+const MAX_GRADIENT_COLORS: usize = 4;
+
+/// Allowed named colors
+const NAMED_COLORS: [&str; 24] = [
+    "black",
+    "white",
+    "gray",
+    "silver",
+    "red",
+    "maroon",
+    "crimson",
+    "orange",
+    "gold",
+    "yellow",
+    "olive",
+    "lime",
+    "green",
+    "teal",
+    "cyan",
+    "navy",
+    "blue",
+    "skyblue",
+    "purple",
+    "violet",
+    "magenta",
+    "pink",
+    "brown",
+    "transparent",
+];
+
+/// Allowed font families, `font=` and the CSS value
+const FONTS: [(&str, &str); 8] = [
+    ("sans", "sans-serif"),
+    ("serif", "serif"),
+    ("mono", "monospace"),
+    ("cursive", "cursive"),
+    ("system", "system-ui, sans-serif"),
+    (
+        "hei",
+        r#""PingFang SC", "Microsoft YaHei", "Noto Sans CJK SC", sans-serif"#,
+    ),
+    (
+        "song",
+        r#""Songti SC", "SimSun", "Noto Serif CJK SC", serif"#,
+    ),
+    ("kai", r#""Kaiti SC", "STKaiti", "KaiTi", serif"#),
+];
+
+#[derive(Debug, Clone)]
+/// Style of the general card
+pub(crate) struct Style {
+    /// Color scheme, the built-in colors on a transparent background if not
+    /// given
+    pub scheme: Option<ColorScheme>,
+
+    /// Text color
+    pub color: Option<Cow<'static, str>>,
+
+    /// Background color, or colors of a diagonal linear gradient
+    pub background: Vec<Cow<'static, str>>,
+
+    /// Border radius of the background, px
+    pub radius: u8,
+
+    /// Font family, CSS value
+    pub font: Option<&'static str>,
+
+    /// Font size, px
+    pub font_size: Option<u8>,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self {
+            scheme: None,
+            color: None,
+            background: Vec::new(),
+            radius: DEFAULT_RADIUS,
+            font: None,
+            font_size: None,
+        }
+    }
+}
+
+impl Style {
+    /// Parse from `theme=`, `color=`, `bg=` (a color, or up to 4 colors
+    /// separated by `,` for a gradient), `radius=`, `font=` and `font_size=`.
+    /// Invalid values are ignored.
+    pub(crate) fn from_queries(queries: &Queries) -> Self {
+        Self {
+            scheme: queries.get("theme").and_then(|theme| theme.parse().ok()),
+            color: queries.get("color").and_then(|color| parse_color(color)),
+            background: queries
+                .get("bg")
+                .and_then(|bg| {
+                    let colors = bg.split(',').map(parse_color).collect::<Option<Vec<_>>>()?;
+
+                    (colors.len() <= MAX_GRADIENT_COLORS).then_some(colors)
+                })
+                .unwrap_or_default(),
+            radius: queries
+                .get("radius")
+                .and_then(|radius| radius.parse().ok())
+                .filter(|radius| *radius <= MAX_RADIUS)
+                .unwrap_or(DEFAULT_RADIUS),
+            font: queries.get("font").and_then(|font| {
+                FONTS
+                    .iter()
+                    .find(|(name, _)| *name == font.as_ref())
+                    .map(|(_, family)| *family)
+            }),
+            font_size: queries
+                .get("font_size")
+                .and_then(|font_size| font_size.parse().ok())
+                .filter(|font_size| FONT_SIZE_RANGE.contains(font_size)),
+        }
+    }
+
+    /// Fill of the background, none for transparent.
+    fn background_fill(&self) -> Option<&str> {
+        match (self.background.as_slice(), self.scheme) {
+            ([], Some(ColorScheme::Light)) => Some(LIGHT.background),
+            ([], Some(ColorScheme::Dark)) => Some(DARK.background),
+            ([], _) => None,
+            ([color], _) => Some(color),
+            _ => Some("url(#card-bg-gradient)"),
+        }
+    }
+
+    /// The CSS, overriding the theme ones.
+    pub(crate) fn css(&self) -> impl StringExtT + '_ {
+        let color = self.color.as_deref().or(match self.scheme {
+            Some(ColorScheme::Dark) => Some(DARK.accent),
+            _ => None,
+        });
+
+        (
+            color.map(|color| {
+                (
+                    "#detail .text{fill:",
+                    color,
+                    ";}#image .line{stroke:",
+                    color,
+                    ";}",
+                )
+            }),
+            self.font
+                .map(|font| ("#detail .text{font-family:", font, ";}")),
+            self.font_size
+                .map(|font_size| ("#detail .text{font-size:", font_size, "px;}")),
+            self.background_fill()
+                .map(|fill| (".card-bg{fill:", fill, ";}")),
+            // Transparent background with default colors
+            (self.scheme == Some(ColorScheme::Auto)
+                && self.color.is_none()
+                && self.background.is_empty())
+            .then_some((
+                "@media (prefers-color-scheme: dark){#detail .text{fill:",
+                DARK.accent,
+                ";}#image .line{stroke:",
+                DARK.accent,
+                ";}}",
+            )),
+        )
+    }
+
+    /// The gradient definition, if any.
+    pub(crate) fn defs(&self) -> Option<String> {
+        (self.background.len() > 1).then(|| {
+            let last = self.background.len() - 1;
+
+            let mut defs = String::from(
+                r#"<linearGradient id="card-bg-gradient" x1="0" y1="0" x2="1" y2="1">"#,
+            );
+            for (idx, color) in self.background.iter().enumerate() {
+                defs.push_str(&str_concat!(
+                    r#"<stop offset=""#,
+                    idx * 100 / last,
+                    r#"%" stop-color=""#,
+                    color.as_ref(),
+                    r#""/>"#
+                ));
+            }
+            defs.push_str("</linearGradient>");
+
+            defs
+        })
+    }
+
+    /// The background, none if transparent.
+    pub(crate) fn background(&self) -> Option<impl StringExtT + use<>> {
+        self.background_fill().is_some().then_some((
+            r#"<rect class="card-bg" width="100%" height="100%" rx=""#,
+            self.radius,
+            r#""/>"#,
+        ))
+    }
+}
+
+/// Parse named or hex color.
+fn parse_color(color: &str) -> Option<Cow<'static, str>> {
+    let color = color.trim();
+
+    NAMED_COLORS
+        .iter()
+        .find(|named| named.eq_ignore_ascii_case(color))
+        .map(|named| Cow::Borrowed(*named))
+        .or_else(|| parse_hex_color(color).map(Cow::Owned))
+}
+
+#[test]
+fn test_style() {
+    let queries = Queries::try_parse(
+        "theme=dark&color=Gold&bg=%23112233,000&radius=12&font=mono&font_size=14",
+    );
+    let style = Style::from_queries(&queries);
+    assert_eq!(style.scheme, Some(ColorScheme::Dark));
+    assert_eq!(style.color.as_deref(), Some("gold"));
+    assert_eq!(style.background, ["#112233", "#000"]);
+    assert_eq!(style.radius, 12);
+    assert_eq!(style.font, Some("monospace"));
+    assert_eq!(style.font_size, Some(14));
+    assert_eq!(
+        style.defs().as_deref(),
+        Some(
+            r##"<linearGradient id="card-bg-gradient" x1="0" y1="0" x2="1" y2="1"><stop offset="0%" stop-color="#112233"/><stop offset="100%" stop-color="#000"/></linearGradient>"##
+        )
+    );
+    assert_eq!(
+        style.css().to_string_ext(),
+        "#detail .text{fill:gold;}#image .line{stroke:gold;}#detail .text{font-family:monospace;}#detail .text{font-size:14px;}.card-bg{fill:url(#card-bg-gradient);}"
+    );
+
+    // Invalid ones are ignored
+    let queries = Queries::try_parse(
+        "theme=neon&color=red%3B%7Dsvg%7B&bg=red,url(x)&radius=99&font=Comic%20Sans&font_size=100",
+    );
+    let style = Style::from_queries(&queries);
+    assert_eq!(style.scheme, None);
+    assert_eq!(style.color, None);
+    assert!(style.background.is_empty());
+    assert_eq!(style.radius, DEFAULT_RADIUS);
+    assert_eq!(style.font, None);
+    assert_eq!(style.font_size, None);
+    assert!(style.background().is_none());
+    assert_eq!(style.css().to_string_ext(), "");
+
+    // Follows `prefers-color-scheme`
+    let style = Style::from_queries(&Queries::try_parse("theme=auto"));
+    assert!(
+        style
+            .css()
+            .to_string_ext()
+            .starts_with("@media (prefers-color-scheme: dark)")
+    );
+}
@@ -0,0 +1,150 @@
+//! Time of day greetings of the general card
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Period of the day, see [`GreetingHours`].
+pub(crate) enum Period {
+    Morning,
+    Noon,
+    Afternoon,
+    Evening,
+    LateNight,
+}
+
+impl Period {
+    /// All periods, in order of the day.
+    const ALL: [Self; 5] = [
+        Self::Morning,
+        Self::Noon,
+        Self::Afternoon,
+        Self::Evening,
+        Self::LateNight,
+    ];
+
+    #[inline]
+    /// Index in [`Messages::greetings`](super::locale::Messages::greetings).
+    pub(crate) const fn index(self) -> usize {
+        self as usize
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+/// The hour (0 ~ 23) each period of the day starts at, in the requested
+/// timezone.
+///
+/// Each period lasts until the next one starts, and the late night one wraps
+/// around midnight to the morning.
+pub(crate) struct GreetingHours {
+    pub morning: u8,
+
+    pub noon: u8,
+
+    pub afternoon: u8,
+
+    pub evening: u8,
+
+    pub late_night: u8,
+}
+
+impl Default for GreetingHours {
+    fn default() -> Self {
+        Self {
+            morning: 5,
+            noon: 11,
+            afternoon: 13,
+            evening: 18,
+            late_night: 23,
+        }
+    }
+}
+
+impl GreetingHours {
+    #[inline]
+    const fn starts(&self) -> [u8; 5] {
+        [
+            self.morning,
+            self.noon,
+            self.afternoon,
+            self.evening,
+            self.late_night,
+        ]
+    }
+
+    #[inline]
+    /// Hours since the morning starts, wrapping around midnight.
+    fn since_morning(&self, hour: u8) -> i16 {
+        (i16::from(hour) - i16::from(self.morning)).rem_euclid(24)
+    }
+
+    /// Whether all hours are within 0 ~ 23 and in order of the day (wrapping
+    /// around midnight).
+    pub(crate) fn is_valid(&self) -> bool {
+        let starts = self.starts();
+
+        starts.iter().all(|&hour| hour < 24)
+            && starts
+                .windows(2)
+                .all(|pair| self.since_morning(pair[0]) < self.since_morning(pair[1]))
+    }
+
+    /// Period of the day at the given hour.
+    pub(crate) fn period(&self, hour: u8) -> Period {
+        let since_morning = self.since_morning(hour);
+
+        Period::ALL
+            .into_iter()
+            .zip(self.starts())
+            .rfind(|&(_, start)| self.since_morning(start) <= since_morning)
+            .map_or(Period::LateNight, |(period, _)| period)
+    }
+}
+
+#[test]
+fn test_period() {
+    let hours = GreetingHours::default();
+    assert!(hours.is_valid());
+
+    assert_eq!(hours.period(3), Period::LateNight);
+    assert_eq!(hours.period(5), Period::Morning);
+    assert_eq!(hours.period(10), Period::Morning);
+    assert_eq!(hours.period(12), Period::Noon);
+    assert_eq!(hours.period(15), Period::Afternoon);
+    assert_eq!(hours.period(18), Period::Evening);
+    assert_eq!(hours.period(23), Period::LateNight);
+
+    // Late night after midnight
+    let hours = GreetingHours {
+        late_night: 1,
+        ..GreetingHours::default()
+    };
+    assert!(hours.is_valid());
+    assert_eq!(hours.period(0), Period::Evening);
+    assert_eq!(hours.period(2), Period::LateNight);
+
+    // Never panics, even with invalid hours
+    let hours = GreetingHours {
+        morning: 255,
+        ..GreetingHours::default()
+    };
+    assert!(!hours.is_valid());
+    let _ = hours.period(0);
+
+    for invalid in [
+        GreetingHours {
+            noon: 24,
+            ..GreetingHours::default()
+        },
+        GreetingHours {
+            noon: 14,
+            ..GreetingHours::default()
+        },
+        GreetingHours {
+            late_night: 5,
+            ..GreetingHours::default()
+        },
+    ] {
+        assert!(!invalid.is_valid(), "{invalid:?}");
+    }
+}